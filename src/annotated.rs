@@ -0,0 +1,278 @@
+//! TOML for people: manifests written out with their docs as comments.
+//!
+//! `to_toml()` is meant for machines. These writers produce the same data, with each table's
+//! localized title and description as a comment above it and each player count explained
+//! beside it. Language maps are written inline with their languages sorted, and everything else
+//! keeps the order its struct declares, so regenerated files diff cleanly. The output is meant
+//! as a starting point for editing by hand.
+//!
+//! ```toml
+//! # Beginners
+//! # A simple game for beginners
+//! [[scenarios]]
+//! name = "beginners"
+//! titles = { en = "Beginners" }
+//!
+//! # Champion
+//! [[scenarios.players]]
+//! name = "champion"
+//! count = "2+[even]" # 2 or more, even ("N", "N-M", or "N+", then "[even]" or "[odd]" if needed)
+//! ```
+use std::str::FromStr;
+
+use serde::Serialize;
+use toml_edit::ser::Error;
+use toml_edit::{ArrayOfTables, Item, Table, Value};
+
+use crate::expansion::ExpansionManifest;
+use crate::game::GameManifest;
+use crate::introspect;
+use crate::peripheral::PeripheralManifest;
+use crate::range::Range;
+
+/// Fields holding a `LanguageMap`, written inline.
+const LANGUAGE_MAPS: &[&str] = &["titles", "descriptions", "labels", "messages"];
+
+/// `Range` fields, by their path with array indices left out.
+const RANGES: &[&str] = &[
+    "scenarios.players.count",
+    "scenarios.teams.size",
+    "scenarios.teams.count",
+    "patches.players.count",
+];
+
+pub fn game_manifest(manifest: &GameManifest, locale: &str) -> Result<String, Error> {
+    write(manifest, locale)
+}
+
+pub fn expansion_manifest(manifest: &ExpansionManifest, locale: &str) -> Result<String, Error> {
+    write(manifest, locale)
+}
+
+pub fn peripheral_manifest(manifest: &PeripheralManifest, locale: &str) -> Result<String, Error> {
+    write(manifest, locale)
+}
+
+fn write(manifest: &impl Serialize, locale: &str) -> Result<String, Error> {
+    let mut document = toml_edit::ser::to_document(manifest)?;
+    let root = document.as_table_mut();
+
+    annotate(root, "", locale);
+
+    // The root table has nowhere to put a comment, so it goes above the first key, which is
+    // always written before any table
+    let header = comment(root, locale);
+    let first = root
+        .iter()
+        .find(|(_, item)| item.is_value())
+        .map(|(key, _)| key.to_string());
+    if let Some(first) = first {
+        if let Some(mut key) = root.key_mut(&first) {
+            key.leaf_decor_mut().set_prefix(header);
+        }
+    }
+
+    Ok(document.to_string())
+}
+
+/// A value as a commented table, ready to go into a document at `path`, such as `scenarios`.
+pub(crate) fn table(value: &impl Serialize, path: &str, locale: &str) -> Result<Table, Error> {
+    let mut table = toml_edit::ser::to_document(value)?.as_table().clone();
+    heading(&mut table, path, locale);
+    Ok(table)
+}
+
+/// Expands nested values into tables, and comments every table found along the way. Empty
+/// ones stay inline, as `values = {}`.
+fn annotate(table: &mut Table, path: &str, locale: &str) {
+    for (key, item) in table.iter_mut() {
+        let path = match path {
+            "" => key.get().to_string(),
+            path => format!("{}.{}", path, key.get()),
+        };
+
+        if LANGUAGE_MAPS.contains(&key.get()) {
+            if let Some(map) = item.as_inline_table_mut() {
+                map.sort_values();
+            }
+            continue;
+        }
+        if RANGES.contains(&path.as_str()) {
+            explain(item);
+            continue;
+        }
+
+        let expanded = match std::mem::take(item) {
+            Item::Value(Value::InlineTable(inline)) if !inline.is_empty() => {
+                Item::Table(inline.into_table())
+            }
+            Item::Value(Value::Array(array))
+                if !array.is_empty() && array.iter().all(Value::is_inline_table) =>
+            {
+                let mut tables = ArrayOfTables::new();
+                for value in array {
+                    if let Value::InlineTable(inline) = value {
+                        tables.push(inline.into_table());
+                    }
+                }
+                Item::ArrayOfTables(tables)
+            }
+            other => other,
+        };
+        *item = expanded;
+
+        match item {
+            Item::Table(child) => heading(child, &path, locale),
+            Item::ArrayOfTables(children) => {
+                for child in children.iter_mut() {
+                    heading(child, &path, locale);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn heading(table: &mut Table, path: &str, locale: &str) {
+    annotate(table, path, locale);
+
+    // Providers have no docs of their own, so they borrow their spec's
+    let mut comment = comment(table, locale);
+    if comment.is_empty() {
+        if let Some(spec) = table.get_mut("spec").and_then(Item::as_table_mut) {
+            comment = self::comment(spec, locale);
+            spec.decor_mut().set_prefix("\n");
+        }
+    }
+
+    table.decor_mut().set_prefix(format!("\n{}", comment));
+}
+
+/// The title and description in `locale`, one `#` line each.
+fn comment(table: &Table, locale: &str) -> String {
+    let mut lines: Vec<String> = vec![];
+
+    for field in ["titles", "descriptions"] {
+        let texts: Vec<(String, String)> = table
+            .get(field)
+            .and_then(Item::as_inline_table)
+            .map(|map| {
+                map.iter()
+                    .filter_map(|(locale, text)| {
+                        Some((locale.to_string(), text.as_str()?.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if let Some(text) = introspect::pick(&texts, locale) {
+            for line in text.lines() {
+                let line = line.trim_end().to_string();
+                if !lines.contains(&line) {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+
+    lines
+        .iter()
+        .map(|line| match line.as_str() {
+            "" => "#\n".to_string(),
+            line => format!("# {}\n", line),
+        })
+        .collect()
+}
+
+/// Puts what a range means, and how to write one, beside it.
+fn explain(item: &mut Item) {
+    let Some(value) = item.as_value_mut() else {
+        return;
+    };
+    let range = match value {
+        Value::String(text) => Range::from_str(text.value()).ok(),
+        Value::Integer(count) => Range::from_str(&count.value().to_string()).ok(),
+        _ => None,
+    };
+
+    if let Some(range) = range {
+        value.decor_mut().set_suffix(format!(
+            " # {} (\"N\", \"N-M\", or \"N+\", then \"[even]\" or \"[odd]\" if needed)",
+            range.describe()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::data::serialization::{Jsonable, Tomlable};
+
+    use crate::annotated;
+    use crate::examples::games::Game;
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::Example;
+    use crate::game::GameManifest;
+    use crate::peripheral::PeripheralManifest;
+
+    #[test]
+    fn it_comments_tables_with_their_docs() {
+        let game = Game::simple_battle().build();
+        let toml = annotated::game_manifest(&game, "en").unwrap();
+
+        assert!(
+            toml.starts_with("# Simple Battle Game\n# A simple battle game with cards and dice\n")
+        );
+        assert!(toml.contains(
+            "\n# Beginners\n# A simple game for beginners\n[[scenarios]]\nname = \"beginners\"\n"
+        ));
+        assert!(toml.contains("\n# Champion\n# The Champion\n[[scenarios.players]]\n"));
+        assert!(toml.contains(
+            "count = \"2+[even]\" # 2 or more, even (\"N\", \"N-M\", or \"N+\", then \"[even]\" or \"[odd]\" if needed)\n"
+        ));
+        assert!(toml.contains("titles = { en = \"Beginners\" }\n"));
+        assert!(toml.contains("\noption_defaults = {}\n"));
+
+        assert_eq!(GameManifest::from_toml(&toml).unwrap(), game);
+    }
+
+    #[test]
+    fn it_comments_providers_with_their_spec() {
+        let peripheral = Peripheral::rf_card_reader().build();
+        let toml = annotated::peripheral_manifest(&peripheral, "es").unwrap();
+
+        // The embedded spec only has English docs
+        let provides = toml.find("[[provides]]").unwrap();
+        assert!(
+            toml[..provides].ends_with("\n# Card\n# A card\n"),
+            "{}",
+            toml
+        );
+
+        assert_eq!(PeripheralManifest::from_toml(&toml).unwrap(), peripheral);
+    }
+
+    #[test]
+    fn it_sorts_languages_however_they_were_inserted() {
+        let write = |titles: &str| {
+            let json = format!(
+                r#"{{"key": "min-game", "version": "9.8.1", "vendor": {{"name": "ravenfire"}}, "titles": {}, "scenarios": []}}"#,
+                titles
+            );
+            annotated::game_manifest(&GameManifest::from_json(&json).unwrap(), "en").unwrap()
+        };
+
+        let forwards =
+            write(r#"{"de": "Schlacht", "en": "Battle", "es": "Batalla", "fr": "Bataille"}"#);
+        let backwards =
+            write(r#"{"fr": "Bataille", "es": "Batalla", "en": "Battle", "de": "Schlacht"}"#);
+
+        assert_eq!(forwards, backwards);
+        assert!(
+            forwards.contains(
+                "titles = { de = \"Schlacht\", en = \"Battle\", es = \"Batalla\", fr = \"Bataille\" }\n"
+            ),
+            "{}",
+            forwards
+        );
+    }
+}
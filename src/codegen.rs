@@ -0,0 +1,542 @@
+//! Rust types generated from a `Spec`, for games to use instead of hand-written mirrors.
+//!
+//! Each property set becomes a struct: the spec's own state, the state each feature adds, and
+//! the payload of every event and command. The spec's state holds each feature's state as an
+//! optional flattened field, so one type reads a device with any set of features enabled.
+//! String `enumerations` become enums. Call `generate_file()` from a game's `build.rs` and
+//! `include!` the output, so a spec change the game has not caught up with is a compile error. Generate from a resolved spec
+//! (`SpecRegistry::resolve()`) when it extends another, or the inherited types are left out.
+//!
+//! ```ignore
+//! // build.rs
+//! let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("card.rs");
+//! manifests::codegen::generate_file("specs/card.json", out).unwrap();
+//! ```
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+
+use serde_json::Value;
+
+use common::data::key::ValidKey;
+use common::data::schema::Property;
+use common::data::serialization::{Jsonable, Tomlable};
+use common::data::LanguageMap;
+
+use crate::introspect::{self, DataKind, PropertyShape};
+use crate::specs::{Feature, Spec};
+
+/// Keywords, including those reserved for later use, written as raw identifiers (`r#type`).
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Names that cannot be raw identifiers either, written with a trailing `_` instead.
+const RESERVED: &[&str] = &["_", "crate", "self", "super", "Self"];
+
+/// The Rust source for every property set in a spec.
+pub fn generate(spec: &Spec) -> String {
+    Generator::new(spec).run()
+}
+
+/// Reads a spec from a `.json` or `.toml` file and writes the generated source to `out`.
+///
+/// Prints `cargo:rerun-if-changed` for the spec, so build scripts only rerun when it changes.
+pub fn generate_file(spec: impl AsRef<Path>, out: impl AsRef<Path>) -> io::Result<()> {
+    let path = spec.as_ref();
+    let text = std::fs::read_to_string(path)?;
+
+    let spec = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => Spec::from_toml(&text),
+        _ => Spec::from_json(&text),
+    }
+    .map_err(|error| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {:?}", path.display(), error),
+        )
+    })?;
+
+    println!("cargo:rerun-if-changed={}", path.display());
+    std::fs::write(out, generate(&spec))
+}
+
+/// `ravenfire.card` becomes `RavenfireCard`, and `set_indicator` becomes `SetIndicator`.
+pub(crate) fn pascal_case(key: &str) -> String {
+    key.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// A field name for a property key, before keywords are escaped.
+fn field_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        .to_lowercase();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// An enum variant for a string value, before keywords are escaped.
+fn variant_name(value: &str) -> String {
+    let mut name = pascal_case(value);
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'V');
+    }
+    name
+}
+
+/// `name`, or `name` with the first free number after it, so keys that come out the same,
+/// such as `hit-points` and `hit_points`, or `Red` and `red`, stay apart.
+fn unique(name: String, taken: &[String]) -> String {
+    if !taken.contains(&name) {
+        return name;
+    }
+    (2..)
+        .map(|number| format!("{}{}", name, number))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or(name)
+}
+
+/// `name` as written in source, escaping keywords.
+fn identifier(name: &str) -> String {
+    if RESERVED.contains(&name) {
+        format!("{}_", name)
+    } else if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+struct Generator<'a> {
+    spec: &'a Spec,
+    /// Prefix for every generated type, from the last part of the spec key
+    prefix: String,
+    /// Enums already written, by name, with their values
+    enums: Vec<(String, Vec<String>)>,
+    coordinates: Vec<&'static str>,
+    types: String,
+}
+
+impl<'a> Generator<'a> {
+    fn new(spec: &'a Spec) -> Self {
+        let key = spec.key().to_string();
+        let prefix = pascal_case(key.rsplit('.').next().unwrap_or(&key));
+
+        Self {
+            spec,
+            prefix,
+            enums: vec![],
+            coordinates: vec![],
+            types: String::new(),
+        }
+    }
+
+    fn run(mut self) -> String {
+        let spec = self.spec;
+        let state = format!("{}State", self.prefix);
+
+        let extensions: Vec<(&Feature, String)> = spec
+            .features()
+            .iter()
+            .filter(|feature| !feature.state().is_empty())
+            .map(|feature| {
+                let name = format!(
+                    "{}{}State",
+                    self.prefix,
+                    pascal_case(&feature.key().to_string())
+                );
+                (feature, name)
+            })
+            .collect();
+
+        self.structure(
+            &state,
+            spec.titles(),
+            spec.descriptions(),
+            "",
+            spec.state(),
+            &extensions,
+        );
+        for (feature, name) in &extensions {
+            self.structure(
+                name,
+                feature.titles(),
+                feature.descriptions(),
+                &format!(
+                    "Part of `{}` when the `{}` feature is enabled",
+                    state,
+                    feature.key()
+                ),
+                feature.state(),
+                &[],
+            );
+        }
+
+        let features = spec.feature_keys();
+        for event in spec.events_with(&features) {
+            self.structure(
+                &format!(
+                    "{}{}Event",
+                    self.prefix,
+                    pascal_case(&event.key().to_string())
+                ),
+                event.titles(),
+                event.descriptions(),
+                "",
+                event.payload(),
+                &[],
+            );
+        }
+        for command in spec.commands_with(&features) {
+            self.structure(
+                &format!(
+                    "{}{}Command",
+                    self.prefix,
+                    pascal_case(&command.key().to_string())
+                ),
+                command.titles(),
+                command.descriptions(),
+                "",
+                command.parameters(),
+                &[],
+            );
+        }
+
+        let mut source = format!(
+            "// Generated from `{}` {} ({}). Do not edit.\n\nuse serde::{{Deserialize, Serialize}};\n",
+            spec.key(),
+            spec.version(),
+            spec.url()
+        );
+        for axes in &self.coordinates {
+            let name = format!("Coordinate{}", axes.split(',').count());
+            let _ = write!(
+                source,
+                "\n#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]\npub struct {} {{\n",
+                name
+            );
+            for axis in axes.split(',') {
+                let _ = writeln!(source, "    pub {}: f64,", axis);
+            }
+            source.push_str("}\n");
+        }
+        source.push_str(&self.types);
+
+        source
+    }
+
+    fn structure(
+        &mut self,
+        name: &str,
+        titles: &LanguageMap,
+        descriptions: &LanguageMap,
+        note: &str,
+        properties: &[Property],
+        extensions: &[(&Feature, String)],
+    ) {
+        let mut fields = String::new();
+        let mut names: Vec<String> = vec![];
+
+        for property in properties {
+            let shape = PropertyShape::of(property);
+            let unescaped = unique(field_name(&shape.key), &names);
+            let field = identifier(&unescaped);
+            names.push(unescaped);
+            let mut kind = self.kind(name, &shape);
+
+            if shape.collection {
+                kind = format!("Vec<{}>", kind);
+            }
+
+            fields.push('\n');
+            fields.push_str(&doc(
+                "    ",
+                introspect::pick(&shape.titles, "en"),
+                introspect::pick(&shape.descriptions, "en"),
+            ));
+            // Only string enumerations become enums, so list the others
+            if shape.enumerations.iter().any(|value| !value.is_string()) {
+                let allowed: Vec<String> =
+                    shape.enumerations.iter().map(Value::to_string).collect();
+                let _ = writeln!(fields, "    /// One of {}", allowed.join(", "));
+            }
+            if let Some(deprecation) = ValidKey::try_from(shape.key.as_str())
+                .ok()
+                .and_then(|key| self.spec.property_deprecation(&key))
+            {
+                let _ = writeln!(fields, "    /// Deprecated since {}", deprecation.since());
+            }
+
+            let renamed = field.trim_start_matches("r#") != shape.key;
+            if shape.optional {
+                kind = format!("Option<{}>", kind);
+                if renamed {
+                    let _ = writeln!(
+                        fields,
+                        "    #[serde(rename = \"{}\", default, skip_serializing_if = \"Option::is_none\")]",
+                        shape.key
+                    );
+                } else {
+                    fields.push_str(
+                        "    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n",
+                    );
+                }
+            } else if renamed {
+                let _ = writeln!(fields, "    #[serde(rename = \"{}\")]", shape.key);
+            }
+            let _ = writeln!(fields, "    pub {}: {},", field, kind);
+        }
+
+        // Flattened, so a feature's state sits next to the spec's and is absent when disabled
+        for (feature, kind) in extensions {
+            let unescaped = unique(field_name(&feature.key().to_string()), &names);
+            let field = identifier(&unescaped);
+            names.push(unescaped);
+
+            fields.push('\n');
+            let _ = writeln!(fields, "    /// With the `{}` feature", feature.key());
+            fields.push_str("    #[serde(flatten)]\n");
+            let _ = writeln!(fields, "    pub {}: Option<{}>,", field, kind);
+        }
+
+        let described = doc(
+            "",
+            introspect::localized(titles, "en"),
+            introspect::localized(descriptions, "en"),
+        );
+        self.types.push('\n');
+        self.types.push_str(&described);
+        if !note.is_empty() {
+            if !described.is_empty() {
+                self.types.push_str("///\n");
+            }
+            let _ = writeln!(self.types, "/// {}", note);
+        }
+        self.types
+            .push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+        if fields.is_empty() {
+            let _ = writeln!(self.types, "pub struct {} {{}}", name);
+        } else {
+            let _ = writeln!(self.types, "pub struct {} {{\n{}}}", name, &fields[1..]);
+        }
+    }
+
+    /// The Rust type of a single value, writing any enum or coordinate type it needs.
+    fn kind(&mut self, owner: &str, shape: &PropertyShape) -> String {
+        let strings: Option<Vec<String>> = shape
+            .enumerations
+            .iter()
+            .map(|value| value.as_str().map(str::to_string))
+            .collect();
+
+        match (&shape.data_type, strings) {
+            (DataKind::String | DataKind::Playable, Some(values)) if !values.is_empty() => {
+                self.enumeration(owner, shape, values)
+            }
+            (DataKind::String | DataKind::Playable, _) => "String".to_string(),
+            (DataKind::Integer, _) => "i64".to_string(),
+            (DataKind::Float, _) => "f64".to_string(),
+            (DataKind::Boolean, _) => "bool".to_string(),
+            (DataKind::Coordinate2, _) => self.coordinate("x,y"),
+            (DataKind::Coordinate3, _) => self.coordinate("x,y,z"),
+            (DataKind::Other(_), _) => "serde_json::Value".to_string(),
+        }
+    }
+
+    fn coordinate(&mut self, axes: &'static str) -> String {
+        if !self.coordinates.contains(&axes) {
+            self.coordinates.push(axes);
+        }
+        format!("Coordinate{}", axes.split(',').count())
+    }
+
+    /// The enum for a property's string enumerations. Properties with the same key and values
+    /// share one enum.
+    fn enumeration(&mut self, owner: &str, shape: &PropertyShape, values: Vec<String>) -> String {
+        let mut name = format!("{}{}", self.prefix, pascal_case(&shape.key));
+        match self.enums.iter().find(|(known, _)| known == &name) {
+            Some((_, known)) if known == &values => return name,
+            Some(_) => name = format!("{}{}", owner, pascal_case(&shape.key)),
+            None => {}
+        }
+        self.enums.push((name.clone(), values.clone()));
+
+        self.types.push('\n');
+        self.types.push_str(&doc(
+            "",
+            introspect::pick(&shape.titles, "en"),
+            introspect::pick(&shape.descriptions, "en"),
+        ));
+        let _ = writeln!(
+            self.types,
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\npub enum {} {{",
+            name
+        );
+        let mut variants: Vec<String> = vec![];
+        for value in &values {
+            let variant = unique(variant_name(value), &variants);
+            let _ = writeln!(
+                self.types,
+                "    #[serde(rename = \"{}\")]\n    {},",
+                value.replace('\\', "\\\\").replace('"', "\\\""),
+                identifier(&variant)
+            );
+            variants.push(variant);
+        }
+        self.types.push_str("}\n");
+
+        name
+    }
+}
+
+/// Doc comment lines from a title and a description.
+fn doc(indent: &str, title: Option<String>, description: Option<String>) -> String {
+    let mut lines = vec![];
+    lines.extend(title);
+    if let Some(description) = description {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(description.lines().map(str::to_string));
+    }
+
+    lines
+        .iter()
+        .map(|line| {
+            if line.is_empty() {
+                format!("{}///\n", indent)
+            } else {
+                format!("{}/// {}\n", indent, line)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use common::data::serialization::Jsonable;
+
+    use crate::codegen::{field_name, generate, identifier, pascal_case, unique};
+    use crate::examples::specs::Spec;
+    use crate::examples::Example;
+
+    /// Generated source, compiled along with the tests so it is known to build.
+    #[allow(dead_code)]
+    mod generated {
+        pub mod card {
+            include!("codegen/card.rs");
+        }
+        pub mod awkward {
+            include!("codegen/awkward.rs");
+        }
+    }
+
+    #[test]
+    fn it_names_types_and_fields() {
+        assert_eq!(pascal_case("set_indicator"), "SetIndicator");
+        assert_eq!(pascal_case("ravenfire.card"), "RavenfireCard");
+        assert_eq!(field_name("hit-points"), "hit_points");
+        assert_eq!(identifier("type"), "r#type");
+        assert_eq!(identifier("try"), "r#try");
+        assert_eq!(identifier("self"), "self_");
+        assert_eq!(identifier("Self"), "Self_");
+        assert_eq!(
+            unique("hit_points".to_string(), &["hit_points".to_string()]),
+            "hit_points2"
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_compiled_output_current() {
+        let awkward = crate::specs::Spec::from_json(include_str!("codegen/awkward.json"))
+            .expect("Failed to build spec");
+
+        for (spec, name, compiled) in [
+            (
+                Spec::card().build(),
+                "card",
+                include_str!("codegen/card.rs"),
+            ),
+            (awkward, "awkward", include_str!("codegen/awkward.rs")),
+        ] {
+            assert!(
+                generate(&spec) == compiled,
+                "src/codegen/{}.rs is out of date, regenerate it with `manifests::codegen::generate_file()`",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn it_round_trips_awkward_keys_through_the_generated_types() {
+        use generated::awkward::{AwkwardColor, AwkwardState};
+
+        let json = serde_json::json!({
+            "type": "t", "self": 1, "try": 0.5, "hit-points": 2, "hit_points": 3,
+            "1st": { "x": 0.0, "y": 1.0 }, "_": ["a"], "color": "red"
+        });
+        let state: AwkwardState = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(state.color, AwkwardColor::Red2);
+        assert_eq!((state.hit_points, state.hit_points2), (2, 3));
+        assert_eq!(serde_json::to_value(&state).unwrap(), json);
+    }
+
+    #[test]
+    fn it_reads_state_with_any_features_enabled() {
+        use generated::card::{CardFacing, CardFacingState, CardState};
+
+        let plain: CardState = serde_json::from_value(serde_json::json!({ "card": "fireball" }))
+            .expect("Failed to read state without features");
+        let json = serde_json::json!({ "card": "fireball", "facing": "up" });
+        let facing: CardState =
+            serde_json::from_value(json.clone()).expect("Failed to read state with `facing`");
+
+        assert_eq!(plain.facing, None);
+        assert_eq!(
+            facing.facing,
+            Some(CardFacingState {
+                facing: CardFacing::Up
+            })
+        );
+        assert_eq!(serde_json::to_value(&facing).unwrap(), json);
+    }
+
+    #[test]
+    fn it_generates_structs_and_enums_for_a_spec() {
+        let source = generate(&Spec::card().build());
+
+        assert!(source.starts_with("// Generated from `ravenfire.card` 1.2.3"));
+        assert!(source.contains(
+            "/// Card\n///\n/// A card\n#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct CardState {\n    /// Card\n    ///\n    /// The card\n    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub card: Option<String>,\n\n    /// With the `facing` feature\n    #[serde(flatten)]\n    pub facing: Option<CardFacingState>,\n}\n"
+        ));
+        assert!(source.contains("/// Facing\n///\n/// The facing of the card\n///\n/// Part of `CardState` when the `facing` feature is enabled\n"));
+        assert!(source.contains("    pub facing: CardFacing,\n"));
+        assert!(source.contains("pub enum CardFacing {\n    #[serde(rename = \"up\")]\n    Up,"));
+        assert!(source.contains("pub struct CardTappedEvent {"));
+        assert!(source.contains("pub struct CardFlippedEvent {"));
+        assert!(source.contains("pub struct CardSetIndicatorCommand {"));
+        assert!(source.contains("    pub brightness: Option<i64>,\n"));
+
+        // The flipped event shares the facing enum with the feature's state
+        assert_eq!(source.matches("pub enum CardFacing").count(), 1);
+        assert!(!source.contains("pub struct CardRfidState"));
+    }
+}
@@ -0,0 +1,84 @@
+// Generated from `ravenfire.awkward` 0.1.0 (https://ravenfire.games/dev/specs/awkward.json). Do not edit.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Coordinate2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Coordinate3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AwkwardColor {
+    #[serde(rename = "Red")]
+    Red,
+    #[serde(rename = "red")]
+    Red2,
+    #[serde(rename = "self")]
+    Self_,
+    #[serde(rename = "2x")]
+    V2x,
+    #[serde(rename = "")]
+    V,
+}
+
+/// Awkward
+///
+/// Keys that are hard to write in Rust
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwkwardState {
+    pub r#type: String,
+
+    #[serde(rename = "self")]
+    pub self_: i64,
+
+    #[serde(rename = "super", default, skip_serializing_if = "Option::is_none")]
+    pub super_: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#try: Option<f64>,
+
+    #[serde(rename = "hit-points")]
+    pub hit_points: i64,
+
+    #[serde(rename = "hit_points")]
+    pub hit_points2: i64,
+
+    #[serde(rename = "1st")]
+    pub _1st: Coordinate2,
+
+    #[serde(rename = "_")]
+    pub __: Vec<String>,
+
+    pub color: AwkwardColor,
+
+    /// With the `crate` feature
+    #[serde(flatten)]
+    pub crate_: Option<AwkwardCrateState>,
+}
+
+/// Part of `AwkwardState` when the `crate` feature is enabled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwkwardCrateState {
+    pub r#gen: i64,
+
+    #[serde(rename = "Crate", default, skip_serializing_if = "Option::is_none")]
+    pub crate_: Option<Coordinate3>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwkwardSelfEvent {
+    pub r#macro: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwkwardDoCommand {
+    pub r#abstract: bool,
+}
@@ -0,0 +1,86 @@
+// Generated from `ravenfire.card` 1.2.3 (https://ravenfire.games/dev/specs/card.json). Do not edit.
+
+use serde::{Deserialize, Serialize};
+
+/// Card
+///
+/// A card
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardState {
+    /// Card
+    ///
+    /// The card
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card: Option<String>,
+
+    /// With the `facing` feature
+    #[serde(flatten)]
+    pub facing: Option<CardFacingState>,
+}
+
+/// Facing
+///
+/// Which direction the card is facing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CardFacing {
+    #[serde(rename = "up")]
+    Up,
+    #[serde(rename = "down")]
+    Down,
+}
+
+/// Facing
+///
+/// The facing of the card
+///
+/// Part of `CardState` when the `facing` feature is enabled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardFacingState {
+    /// Facing
+    ///
+    /// Which direction the card is facing
+    pub facing: CardFacing,
+}
+
+/// Card tapped
+///
+/// A card was tapped on the slot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardTappedEvent {
+    /// Card
+    pub card: String,
+}
+
+/// Card flipped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardFlippedEvent {
+    pub facing: CardFacing,
+}
+
+/// Color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CardColor {
+    #[serde(rename = "off")]
+    Off,
+    #[serde(rename = "red")]
+    Red,
+    #[serde(rename = "green")]
+    Green,
+    #[serde(rename = "blue")]
+    Blue,
+    #[serde(rename = "yellow")]
+    Yellow,
+}
+
+/// Set indicator
+///
+/// Lights the indicator in a color
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardSetIndicatorCommand {
+    /// Color
+    pub color: CardColor,
+
+    /// Brightness
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<i64>,
+}
@@ -0,0 +1,299 @@
+//! Rules that span more than one player type in a `Scenario`.
+//!
+//! `ScenarioPlayer.count` only describes a single player type. Teams and count constraints
+//! describe how the player types relate to each other, such as "teams of 2, up to 4 teams"
+//! or "champions must equal 2× goblins".
+use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
+
+use common::data::key::ValidKey;
+use common::data::LanguageMap;
+use common::macros::{Jsonable, Streamable, Tomlable};
+
+use crate::range::Range;
+use crate::PlayerType;
+
+/// A group of players that play together.
+///
+/// Every team in a scenario is formed from the same pool of player types, so
+/// `size = "2"` and `count = "1-4"` reads as "teams of 2, up to 4 teams".
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Team {
+    name: ValidKey,
+    #[serde(default)]
+    #[builder(default)]
+    titles: LanguageMap,
+    #[serde(default)]
+    #[builder(default)]
+    descriptions: LanguageMap,
+    /// The player types that make up the teams. Empty means every player type in the scenario.
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    players: Vec<ValidKey>,
+    /// How many players are on each team
+    size: Range,
+    /// How many teams are formed
+    count: Range,
+}
+
+impl Team {
+    /// Whether `members` players can be split into teams that satisfy both `size` and `count`.
+    pub fn accepts(&self, members: u16) -> bool {
+        // reachable[n] is true when `n` players can be split into the teams placed so far
+        let target = members as usize;
+        let mut reachable = vec![false; target + 1];
+        reachable[0] = true;
+
+        let max_teams = self
+            .count
+            .upper_bound()
+            .map(u16::from)
+            .unwrap_or(members.max(1));
+        for teams in 1..=max_teams {
+            let mut next = vec![false; target + 1];
+            for (placed, _) in reachable.iter().enumerate().filter(|(_, ok)| **ok) {
+                for size in 1..=(target - placed) {
+                    if size <= u8::MAX as usize && self.size.contains(size as u8) {
+                        next[placed + size] = true;
+                    }
+                }
+            }
+            reachable = next;
+
+            if teams <= u8::MAX as u16 && self.count.contains(teams as u8) && reachable[target] {
+                return true;
+            }
+        }
+
+        members == 0 && self.count.contains(0)
+    }
+
+    /// Whether the given player type is part of this team.
+    pub fn includes(&self, player: &str) -> bool {
+        self.players.is_empty() || self.players.iter().any(|p| p.to_string() == player)
+    }
+}
+
+/// How the two sides of a `CountConstraint` compare.
+#[derive(Tomlable, Jsonable, Streamable, Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub enum Relation {
+    Equal,
+    AtLeast,
+    AtMost,
+}
+
+/// A single `coefficient × count(player)` term of a `CountConstraint`.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Term {
+    player: ValidKey,
+    #[serde(default = "Term::default_coefficient")]
+    #[builder(default = "1")]
+    coefficient: i16,
+}
+
+impl Term {
+    fn default_coefficient() -> i16 {
+        1
+    }
+}
+
+/// A linear rule between player type counts: `sum(terms) <relation> constant`.
+///
+/// "champions must equal 2× goblins" is `champion - 2 goblin == 0`, and
+/// "one game master per 2–5 adventurers" is the pair `adventurer - 2 game_master >= 0`
+/// and `adventurer - 5 game_master <= 0`.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct CountConstraint {
+    #[serde(default)]
+    #[builder(default)]
+    descriptions: LanguageMap,
+    terms: Vec<Term>,
+    relation: Relation,
+    #[serde(default)]
+    #[builder(default)]
+    constant: i16,
+}
+
+impl CountConstraint {
+    /// Whether the constraint holds for the given composition.
+    pub fn holds(&self, composition: &PlayerComposition) -> bool {
+        let total: i32 = self
+            .terms
+            .iter()
+            .map(|term| {
+                i32::from(term.coefficient)
+                    * i32::from(composition.count_of(&term.player.to_string()))
+            })
+            .sum();
+        let constant = i32::from(self.constant);
+
+        match self.relation {
+            Relation::Equal => total == constant,
+            Relation::AtLeast => total >= constant,
+            Relation::AtMost => total <= constant,
+        }
+    }
+}
+
+impl std::fmt::Display for CountConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let terms: Vec<String> = self
+            .terms
+            .iter()
+            .map(|term| format!("{}×{}", term.coefficient, term.player))
+            .collect();
+        let relation = match self.relation {
+            Relation::Equal => "==",
+            Relation::AtLeast => ">=",
+            Relation::AtMost => "<=",
+        };
+
+        write!(f, "{} {} {}", terms.join(" + "), relation, self.constant)
+    }
+}
+
+/// How many players of each type are at the table.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PlayerComposition {
+    counts: Vec<(PlayerType, u8)>,
+}
+
+impl PlayerComposition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the count for a player type, replacing any previous count.
+    pub fn with(mut self, player: &str, count: u8) -> Self {
+        match self.counts.iter_mut().find(|(name, _)| name == player) {
+            Some(entry) => entry.1 = count,
+            None => self.counts.push((player.to_string(), count)),
+        }
+        self
+    }
+
+    /// The number of players of the given type. Unlisted types have 0 players.
+    pub fn count_of(&self, player: &str) -> u8 {
+        self.counts
+            .iter()
+            .find(|(name, _)| name == player)
+            .map(|(_, count)| *count)
+            .unwrap_or(0)
+    }
+
+    pub fn counts(&self) -> &[(PlayerType, u8)] {
+        &self.counts
+    }
+
+    pub fn total(&self) -> u16 {
+        self.counts.iter().map(|(_, count)| u16::from(*count)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::composition::{
+        CountConstraintBuilder, PlayerComposition, Relation, TeamBuilder, TermBuilder,
+    };
+    use crate::range::Range;
+
+    #[test]
+    fn it_splits_players_into_teams() {
+        let team = TeamBuilder::default()
+            .name("teams".try_into().unwrap())
+            .size(Range::from_str("2").unwrap())
+            .count(Range::from_str("1-4").unwrap())
+            .build()
+            .unwrap();
+
+        assert!(team.accepts(2));
+        assert!(team.accepts(8));
+        assert!(!team.accepts(3));
+        assert!(!team.accepts(10));
+    }
+
+    #[test]
+    fn it_splits_players_into_uneven_teams() {
+        let team = TeamBuilder::default()
+            .name("teams".try_into().unwrap())
+            .size(Range::from_str("2-3").unwrap())
+            .count(Range::from_str("2").unwrap())
+            .build()
+            .unwrap();
+
+        assert!(!team.accepts(3));
+        assert!(team.accepts(5));
+        assert!(team.accepts(6));
+        assert!(!team.accepts(7));
+    }
+
+    #[test]
+    fn it_checks_a_linear_constraint() {
+        let constraint = CountConstraintBuilder::default()
+            .terms(vec![
+                TermBuilder::default()
+                    .player("champion".try_into().unwrap())
+                    .build()
+                    .unwrap(),
+                TermBuilder::default()
+                    .player("goblin".try_into().unwrap())
+                    .coefficient(-2)
+                    .build()
+                    .unwrap(),
+            ])
+            .relation(Relation::Equal)
+            .build()
+            .unwrap();
+
+        let legal = PlayerComposition::new()
+            .with("champion", 4)
+            .with("goblin", 2);
+        let illegal = PlayerComposition::new()
+            .with("champion", 3)
+            .with("goblin", 2);
+
+        assert!(constraint.holds(&legal));
+        assert!(!constraint.holds(&illegal));
+    }
+}
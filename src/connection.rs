@@ -0,0 +1,278 @@
+//! How a hub reaches a peripheral: BLE, USB HID, serial, or the network.
+//!
+//! Connections also work in reverse. When hardware is plugged in or discovered, `identify()`
+//! maps the identifier it reported back to the `PeripheralManifest` that declares it.
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use common::data::key::ValidKey;
+use common::macros::{Jsonable, Streamable, Tomlable};
+
+use crate::peripheral::PeripheralManifest;
+
+/// The Bluetooth base UUID that 16 and 32 bit service UUIDs are shorthand for.
+const BLUETOOTH_BASE_UUID: &str = "-0000-1000-8000-00805f9b34fb";
+
+/// One way to connect to a peripheral.
+#[derive(Tomlable, Jsonable, Streamable, Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Connection {
+    /// Bluetooth Low Energy GATT services, as 16, 32, or 128 bit UUIDs
+    Ble {
+        services: Vec<String>,
+    },
+    /// USB HID, with vendor and product ids as hex strings ("0x1a2b")
+    UsbHid {
+        vendor_id: String,
+        product_id: String,
+    },
+    Serial {
+        baud_rate: u32,
+    },
+    /// A network service, found by mDNS service type ("_ravenfire._tcp") and/or port
+    Tcp {
+        #[serde(default)]
+        mdns_service: Option<String>,
+        #[serde(default)]
+        port: Option<u16>,
+    },
+    /// Transports this crate does not know about yet
+    Other {
+        kind: ValidKey,
+        #[serde(default)]
+        parameters: BTreeMap<String, String>,
+    },
+}
+
+/// An identifier a hub saw while scanning for hardware.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Discovered {
+    Usb { vendor_id: u16, product_id: u16 },
+    Ble { service: String },
+    Mdns { service: String },
+}
+
+impl Connection {
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+
+        match self {
+            Connection::Ble { services } => {
+                if services.is_empty() {
+                    errors.push("ble connections need at least one service".to_string());
+                }
+                for service in services {
+                    if normalize_uuid(service).is_none() {
+                        errors.push(format!("`{}` is not a valid BLE service UUID", service));
+                    }
+                }
+            }
+            Connection::UsbHid {
+                vendor_id,
+                product_id,
+            } => {
+                if parse_usb_id(vendor_id).is_none() {
+                    errors.push(format!("`{}` is not a valid USB vendor id", vendor_id));
+                }
+                if parse_usb_id(product_id).is_none() {
+                    errors.push(format!("`{}` is not a valid USB product id", product_id));
+                }
+            }
+            Connection::Serial { baud_rate } => {
+                if *baud_rate == 0 {
+                    errors.push("serial baud rate must be greater than 0".to_string());
+                }
+            }
+            Connection::Tcp { mdns_service, port } => {
+                if mdns_service.is_none() && port.is_none() {
+                    errors.push("tcp connections need an mDNS service or a port".to_string());
+                }
+                if let Some(service) = mdns_service {
+                    if !is_mdns_service(service) {
+                        errors.push(format!(
+                            "`{}` is not an mDNS service type like `_name._tcp`",
+                            service
+                        ));
+                    }
+                }
+            }
+            Connection::Other { .. } => {}
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Whether a discovered identifier belongs to this connection.
+    pub fn matches(&self, discovered: &Discovered) -> bool {
+        match (self, discovered) {
+            (
+                Connection::UsbHid {
+                    vendor_id,
+                    product_id,
+                },
+                Discovered::Usb {
+                    vendor_id: found_vendor,
+                    product_id: found_product,
+                },
+            ) => {
+                parse_usb_id(vendor_id) == Some(*found_vendor)
+                    && parse_usb_id(product_id) == Some(*found_product)
+            }
+            (Connection::Ble { services }, Discovered::Ble { service }) => {
+                let found = normalize_uuid(service);
+                found.is_some() && services.iter().any(|s| normalize_uuid(s) == found)
+            }
+            (
+                Connection::Tcp {
+                    mdns_service: Some(declared),
+                    ..
+                },
+                Discovered::Mdns { service },
+            ) => trim_mdns(declared) == trim_mdns(service),
+            _ => false,
+        }
+    }
+}
+
+/// Finds the manifest that declares a discovered identifier.
+///
+/// When several versions of a model match, the newest one wins.
+pub fn identify<'a>(
+    manifests: &'a [PeripheralManifest],
+    discovered: &Discovered,
+) -> Option<&'a PeripheralManifest> {
+    manifests
+        .iter()
+        .filter(|manifest| {
+            manifest
+                .connections()
+                .iter()
+                .any(|connection| connection.matches(discovered))
+        })
+        .max_by(|a, b| a.version().cmp(b.version()))
+}
+
+/// "0x1A2B" or "1a2b" as a 16 bit id
+fn parse_usb_id(id: &str) -> Option<u16> {
+    let digits = id
+        .strip_prefix("0x")
+        .or_else(|| id.strip_prefix("0X"))
+        .unwrap_or(id);
+
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    u16::from_str_radix(digits, 16).ok()
+}
+
+/// Expands 16 and 32 bit UUIDs to the full, lowercase, 128 bit form.
+fn normalize_uuid(uuid: &str) -> Option<String> {
+    let uuid = uuid.to_ascii_lowercase();
+    let short = uuid.strip_prefix("0x").unwrap_or(&uuid);
+    let is_hex = |s: &str| s.chars().all(|c| c.is_ascii_hexdigit());
+
+    if (short.len() == 4 || short.len() == 8) && is_hex(short) {
+        return Some(format!("{:0>8}{}", short, BLUETOOTH_BASE_UUID));
+    }
+
+    let groups: Vec<&str> = uuid.split('-').collect();
+    let lengths: Vec<usize> = groups.iter().map(|group| group.len()).collect();
+    if lengths == [8, 4, 4, 4, 12] && groups.iter().all(|group| is_hex(group)) {
+        return Some(uuid);
+    }
+
+    None
+}
+
+fn is_mdns_service(service: &str) -> bool {
+    let labels: Vec<&str> = trim_mdns(service).split('.').collect();
+
+    labels.len() == 2
+        && labels[0].len() > 1
+        && labels[0].starts_with('_')
+        && (labels[1] == "_tcp" || labels[1] == "_udp")
+}
+
+/// mDNS service types are often written with a trailing ".local."
+fn trim_mdns(service: &str) -> &str {
+    service
+        .trim_end_matches('.')
+        .trim_end_matches(".local")
+        .trim_end_matches('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connection::{identify, Connection, Discovered};
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::Example;
+
+    #[test]
+    fn it_validates_identifier_formats() {
+        let usb = Connection::UsbHid {
+            vendor_id: "0x1a2b".to_string(),
+            product_id: "12345".to_string(),
+        };
+        let ble = Connection::Ble {
+            services: vec![
+                "180F".to_string(),
+                "6e400001-b5a3-f393-e0a9-e50e24dcca9e".to_string(),
+                "not-a-uuid".to_string(),
+            ],
+        };
+
+        assert_eq!(
+            usb.validate(),
+            Err(vec!["`12345` is not a valid USB product id".to_string()])
+        );
+        assert_eq!(
+            ble.validate(),
+            Err(vec![
+                "`not-a-uuid` is not a valid BLE service UUID".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn it_identifies_plugged_in_hardware() {
+        let manifests = vec![
+            Peripheral::rf_screen().build(),
+            Peripheral::rf_card_reader().build(),
+        ];
+
+        let usb = Discovered::Usb {
+            vendor_id: 0x1a2b,
+            product_id: 0x0001,
+        };
+        let ble = Discovered::Ble {
+            service: "6E400001-B5A3-F393-E0A9-E50E24DCCA9E".to_string(),
+        };
+        let unknown = Discovered::Usb {
+            vendor_id: 0x1a2b,
+            product_id: 0x0002,
+        };
+
+        assert_eq!(identify(&manifests, &usb), Some(&manifests[1]));
+        assert_eq!(identify(&manifests, &ble), Some(&manifests[1]));
+        assert_eq!(identify(&manifests, &unknown), None);
+    }
+
+    #[test]
+    fn it_matches_mdns_services_with_a_domain() {
+        let tcp = Connection::Tcp {
+            mdns_service: Some("_ravenfire._tcp".to_string()),
+            port: None,
+        };
+
+        assert_eq!(tcp.validate(), Ok(()));
+        assert!(tcp.matches(&Discovered::Mdns {
+            service: "_ravenfire._tcp.local.".to_string()
+        }));
+    }
+}
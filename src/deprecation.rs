@@ -0,0 +1,158 @@
+//! Retiring specs, features, properties, and manifests without silently breaking anyone.
+//!
+//! A deprecated item keeps working. Validation reports it as a warning, and the `Matcher`
+//! only hands out deprecated providers when nothing else fits.
+use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
+
+use common::chrono::NaiveDate;
+use common::data::key::ValidKey;
+use common::data::LanguageMap;
+use common::macros::{Jsonable, Streamable, Tomlable};
+use common::semver::Version;
+
+use crate::introspect;
+
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Deprecation {
+    /// The first version of the spec or manifest that deprecates the item
+    since: Version,
+    /// What to use instead, such as `rfid` for `nfc`
+    #[serde(default)]
+    #[builder(default)]
+    replacement: Option<ValidKey>,
+    /// When the item is expected to be gone
+    /// "2025-06-30"
+    #[serde(default)]
+    #[builder(default)]
+    removal: Option<NaiveDate>,
+    /// Why, and what to do about it
+    /// { en: "NFC readers are being replaced by RFID" }
+    #[serde(default)]
+    #[builder(default)]
+    messages: LanguageMap,
+}
+
+/// Deprecation of a single property, by key. `Property` lives in `common`, so its
+/// deprecation sits next to it.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct DeprecatedProperty {
+    key: ValidKey,
+    #[serde(flatten)]
+    deprecation: Deprecation,
+}
+
+impl Deprecation {
+    /// The message in a locale, falling back to English or any message there is.
+    pub fn message(&self, locale: &str) -> Option<String> {
+        introspect::localized(&self.messages, locale)
+    }
+
+    /// Whether the removal date has come by `today`.
+    pub fn is_removed(&self, today: NaiveDate) -> bool {
+        self.removed_on(today).is_some()
+    }
+
+    /// The removal date, once it has come by `today`.
+    pub fn removed_on(&self, today: NaiveDate) -> Option<NaiveDate> {
+        self.removal.filter(|removal| *removal <= today)
+    }
+
+    /// A warning about using `subject`, such as "the `nfc` feature", with the message in
+    /// `locale` when there is one. The removal date reads as past once it has come by `today`;
+    /// without a date it reads as planned.
+    pub fn warning(&self, subject: &str, locale: &str, today: Option<NaiveDate>) -> String {
+        let mut warning = format!("{} is deprecated since {}", subject, self.since);
+
+        if let Some(replacement) = &self.replacement {
+            warning.push_str(&format!(", use `{}` instead", replacement));
+        }
+        if let Some(removal) = today.and_then(|today| self.removed_on(today)) {
+            warning.push_str(&format!(", and was removed on {}", removal));
+        } else if let Some(removal) = &self.removal {
+            warning.push_str(&format!(", and will be removed on {}", removal));
+        }
+        if let Some(message) = self.message(locale) {
+            warning.push_str(&format!(": {}", message));
+        }
+
+        warning
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::chrono::NaiveDate;
+
+    use crate::deprecation::Deprecation;
+
+    #[test]
+    fn it_describes_what_to_do_instead() {
+        let deprecation: Deprecation = serde_json::from_str(
+            r#"
+            {
+                "since": "1.3.0",
+                "replacement": "rfid",
+                "removal": "2025-06-30",
+                "messages": {
+                    "en": "NFC readers are being replaced",
+                    "es": "Los lectores NFC se están reemplazando"
+                }
+            }
+            "#,
+        )
+        .expect("Failed to parse deprecation");
+
+        assert_eq!(
+            deprecation.warning("the `nfc` feature", "en", None),
+            "the `nfc` feature is deprecated since 1.3.0, use `rfid` instead, and will be removed on 2025-06-30: NFC readers are being replaced"
+        );
+        assert!(deprecation
+            .warning("the `nfc` feature", "es", None)
+            .ends_with(": Los lectores NFC se están reemplazando"));
+        assert!(!deprecation.is_removed(NaiveDate::from_ymd_opt(2025, 6, 29).unwrap()));
+        assert!(deprecation.is_removed(NaiveDate::from_ymd_opt(2025, 6, 30).unwrap()));
+        assert_eq!(
+            deprecation.warning(
+                "the `nfc` feature",
+                "en",
+                NaiveDate::from_ymd_opt(2025, 6, 29)
+            ),
+            deprecation.warning("the `nfc` feature", "en", None)
+        );
+        assert_eq!(
+            deprecation.warning(
+                "the `nfc` feature",
+                "en",
+                NaiveDate::from_ymd_opt(2025, 7, 1)
+            ),
+            "the `nfc` feature is deprecated since 1.3.0, use `rfid` instead, and was removed on 2025-06-30: NFC readers are being replaced"
+        );
+    }
+}
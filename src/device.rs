@@ -0,0 +1,187 @@
+//! Physical devices and the peripheral models they are built from.
+//!
+//! A `PeripheralManifest` describes a model. Each physical device is a lightweight
+//! `DeviceInstance` that points at its model by key and version, which is what a hub stores.
+use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
+
+use common::data::key::ValidKey;
+use common::macros::{Jsonable, Streamable, Tomlable};
+use common::semver::Version;
+
+use crate::peripheral::PeripheralManifest;
+
+/// A single physical peripheral.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct DeviceInstance {
+    /// Serial number of this device
+    uuid: ValidKey,
+
+    /// Key of the `PeripheralManifest` this device is an instance of
+    model: ValidKey,
+
+    /// Version of the `PeripheralManifest` this device is an instance of
+    model_version: Version,
+
+    /// Firmware currently installed, if the device reports it
+    #[serde(default)]
+    #[builder(setter(into, strip_option), default)]
+    firmware: Option<Version>,
+
+    /// A name the owner gave this device, such as "Kitchen table reader"
+    #[serde(default)]
+    #[builder(setter(into, strip_option), default)]
+    nickname: Option<String>,
+}
+
+impl DeviceInstance {
+    /// Whether this device is an instance of the given model.
+    pub fn is_instance_of(&self, model: &PeripheralManifest) -> bool {
+        &self.model == model.key() && &self.model_version == model.version()
+    }
+}
+
+/// A device joined to its model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device<'a> {
+    pub instance: &'a DeviceInstance,
+    pub model: &'a PeripheralManifest,
+}
+
+/// The models and devices a hub knows about.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Clone,
+    PartialEq,
+    Default,
+)]
+#[getset(get = "pub")]
+pub struct Inventory {
+    #[serde(default)]
+    models: Vec<PeripheralManifest>,
+    #[serde(default)]
+    devices: Vec<DeviceInstance>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a model, replacing any model with the same key and version.
+    pub fn add_model(&mut self, model: PeripheralManifest) {
+        self.models.retain(|existing| {
+            existing.key() != model.key() || existing.version() != model.version()
+        });
+        self.models.push(model);
+    }
+
+    /// Adds a device, replacing any device with the same serial number.
+    pub fn add_device(&mut self, device: DeviceInstance) {
+        self.devices.retain(|existing| existing.uuid != device.uuid);
+        self.devices.push(device);
+    }
+
+    /// Removes a device by serial number, returning it if it was present.
+    pub fn remove_device(&mut self, uuid: &ValidKey) -> Option<DeviceInstance> {
+        let index = self
+            .devices
+            .iter()
+            .position(|device| &device.uuid == uuid)?;
+        Some(self.devices.remove(index))
+    }
+
+    pub fn model_of(&self, device: &DeviceInstance) -> Option<&PeripheralManifest> {
+        self.models
+            .iter()
+            .find(|model| device.is_instance_of(model))
+    }
+
+    /// Every device whose model is known, joined to that model.
+    pub fn resolved(&self) -> Vec<Device<'_>> {
+        self.devices
+            .iter()
+            .filter_map(|instance| {
+                Some(Device {
+                    instance,
+                    model: self.model_of(instance)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Devices whose model (or model version) has not been added yet.
+    pub fn unresolved(&self) -> Vec<&DeviceInstance> {
+        self.devices
+            .iter()
+            .filter(|device| self.model_of(device).is_none())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::Inventory;
+    use crate::examples::devices::Device;
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::Example;
+
+    #[test]
+    fn it_joins_devices_to_their_models() {
+        let mut inventory = Inventory::new();
+        inventory.add_model(Peripheral::rf_card_reader().build());
+        inventory.add_model(Peripheral::rf_screen().build());
+        inventory.add_device(Device::rf_card_reader().build());
+        inventory.add_device(Device::min().build());
+
+        let resolved = inventory.resolved();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].model, &Peripheral::rf_card_reader().build());
+        assert_eq!(inventory.unresolved(), vec![&Device::min().build()]);
+    }
+
+    #[test]
+    fn it_does_not_join_other_model_versions() {
+        let mut model = Peripheral::rf_card_reader().build();
+        model.set_version("3.0.0".parse().unwrap());
+
+        let mut inventory = Inventory::new();
+        inventory.add_model(model);
+        inventory.add_device(Device::rf_card_reader().build());
+
+        assert!(inventory.resolved().is_empty());
+    }
+
+    #[test]
+    fn it_replaces_devices_with_the_same_serial_number() {
+        let mut renamed = Device::rf_card_reader().build();
+        renamed.set_nickname(Some("Game room reader".to_string()));
+
+        let mut inventory = Inventory::new();
+        inventory.add_device(Device::rf_card_reader().build());
+        inventory.add_device(renamed.clone());
+
+        assert_eq!(inventory.devices(), &vec![renamed.clone()]);
+        assert_eq!(inventory.remove_device(renamed.uuid()), Some(renamed));
+        assert!(inventory.devices().is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+//! What changed between two releases of a manifest, and whether the version bump says so.
+//!
+//! Each `Change` carries the semver `Impact` it has on anyone depending on the manifest through
+//! a `VersionReq`. `Diff::check()` fails when the declared version bump is smaller than the
+//! largest impact, so a breaking change cannot ship as a minor release.
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use common::macros::{Jsonable, Streamable, Tomlable};
+use common::semver::Version;
+
+pub mod game;
+pub mod peripheral;
+pub mod spec;
+
+/// How much of a version bump a change needs.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Impact {
+    /// Wording and metadata
+    Patch,
+    /// Backwards compatible additions
+    Minor,
+    /// Anything that can break an existing dependent
+    Major,
+}
+
+impl Display for Impact {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Impact::Patch => write!(f, "patch"),
+            Impact::Minor => write!(f, "minor"),
+            Impact::Major => write!(f, "major"),
+        }
+    }
+}
+
+/// A single difference between two releases.
+#[derive(Tomlable, Jsonable, Streamable, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Change {
+    pub impact: Impact,
+    /// Where the change is, such as `features.facing.state.facing`
+    pub path: String,
+    pub description: String,
+    /// What the change means for tables, such as "drops support for tables without a screen"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Change {
+    pub(crate) fn new(
+        impact: Impact,
+        path: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            impact,
+            path: path.into(),
+            description: description.into(),
+            hint: None,
+        }
+    }
+
+    pub(crate) fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+/// Every change between two releases of one manifest.
+#[derive(Tomlable, Jsonable, Streamable, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Diff {
+    /// What was compared, such as a spec URL or a manifest key
+    pub subject: String,
+    pub from: Version,
+    pub to: Version,
+    pub changes: Vec<Change>,
+}
+
+impl Diff {
+    /// The largest impact of any change, if anything changed.
+    pub fn impact(&self) -> Option<Impact> {
+        self.changes.iter().map(|change| change.impact).max()
+    }
+
+    /// The bump `from` to `to` declares, if the version went up.
+    ///
+    /// Before 1.0.0, `^0.y.z` requirements only accept patch releases, so a minor release is
+    /// breaking and a patch release may add things. Before 0.1.0, `^0.0.z` accepts nothing but
+    /// `0.0.z` itself, so every release is breaking.
+    pub fn declared(&self) -> Option<Impact> {
+        if self.to <= self.from {
+            return None;
+        }
+
+        let (from, to) = (&self.from, &self.to);
+        Some(
+            if from.major != to.major
+                || (from.major == 0 && from.minor != to.minor)
+                || (from.major == 0 && from.minor == 0)
+            {
+                Impact::Major
+            } else if from.minor != to.minor || (from.major == 0 && from.patch != to.patch) {
+                Impact::Minor
+            } else {
+                Impact::Patch
+            },
+        )
+    }
+
+    /// Fails when the declared version bump is too small for the changes.
+    pub fn check(&self) -> Result<(), Vec<String>> {
+        if self.to < self.from {
+            return Err(vec![format!(
+                "`{}` went backwards from {} to {}",
+                self.subject, self.from, self.to
+            )]);
+        }
+
+        let declared = self.declared();
+        let errors: Vec<String> = self
+            .changes
+            .iter()
+            .filter(|change| Some(change.impact) > declared)
+            .map(|change| match declared {
+                Some(declared) => format!(
+                    "`{}`: {} needs a {} release, but {} to {} is a {} release",
+                    change.path, change.description, change.impact, self.from, self.to, declared
+                ),
+                None => format!(
+                    "`{}`: {} needs a {} release, but the version is still {}",
+                    change.path, change.description, change.impact, self.to
+                ),
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// A changelog, with what the release means for tables first.
+impl Display for Diff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} -> {}", self.subject, self.from, self.to)?;
+        match self.impact() {
+            Some(impact) => writeln!(f, " ({} changes)", impact)?,
+            None => return writeln!(f, " (no changes)"),
+        }
+
+        let hints: Vec<&String> = self
+            .changes
+            .iter()
+            .filter_map(|change| change.hint.as_ref())
+            .collect();
+        if !hints.is_empty() {
+            writeln!(f, "\nImpact:")?;
+            for hint in hints {
+                writeln!(f, "- {}", hint)?;
+            }
+        }
+
+        writeln!(f, "\nChanges:")?;
+        for change in &self.changes {
+            writeln!(
+                f,
+                "- [{}] {}: {}",
+                change.impact, change.path, change.description
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,653 @@
+//! Diffs between two releases of a `GameManifest`.
+//!
+//! A game release is major when a table that could play it before cannot anymore: a scenario
+//! is gone, a player count is no longer allowed, or a scenario needs more hardware.
+use common::data::key::ValidKey;
+use common::semver::{Version, VersionReq};
+
+use crate::diff::spec::{properties, Flow};
+use crate::diff::{Change, Diff, Impact};
+use crate::game::{GameManifest, Requirement, Scenario, ScenarioPlayer};
+use crate::range::Range;
+use crate::specs::SpecIdentifier;
+
+/// Counts listed in a hint before the rest are summed up as "and more".
+const LISTED_COUNTS: usize = 8;
+
+/// Every change between two releases of a game.
+pub fn diff(old: &GameManifest, new: &GameManifest) -> Diff {
+    let mut changes = vec![];
+
+    if old.vendor() != new.vendor() {
+        changes.push(Change::new(
+            Impact::Patch,
+            "vendor",
+            "vendor contact changed",
+        ));
+    }
+    if old.titles() != new.titles() || old.descriptions() != new.descriptions() {
+        changes.push(Change::new(
+            Impact::Patch,
+            "titles",
+            "titles or descriptions changed",
+        ));
+    }
+    if old.url() != new.url() || old.support() != new.support() {
+        changes.push(Change::new(Impact::Patch, "url", "links changed"));
+    }
+
+    runtime(
+        old.requires_runtime(),
+        new.requires_runtime(),
+        "runtime",
+        &mut changes,
+    );
+    capabilities(
+        old.requires_capabilities(),
+        new.requires_capabilities(),
+        &mut changes,
+    );
+
+    // Hosts send options, so they flow into the game like command parameters
+    properties(
+        "options",
+        old.options(),
+        new.options(),
+        Flow::Accepted,
+        &mut changes,
+    );
+
+    for removed in old
+        .scenarios()
+        .iter()
+        .filter(|o| !new.scenarios().iter().any(|n| n.name() == o.name()))
+    {
+        changes.push(
+            Change::new(
+                Impact::Major,
+                format!("scenarios.{}", removed.name()),
+                "scenario removed",
+            )
+            .with_hint(format!(
+                "the `{}` scenario can no longer be played",
+                removed.name()
+            )),
+        );
+    }
+
+    for scenario in new.scenarios() {
+        let path = format!("scenarios.{}", scenario.name());
+        match old.scenarios().iter().find(|o| o.name() == scenario.name()) {
+            None => changes.push(Change::new(Impact::Minor, path, "scenario added")),
+            Some(before) => self::scenario(&path, before, scenario, &mut changes),
+        }
+    }
+
+    Diff {
+        subject: new.key().to_string(),
+        from: old.version().clone(),
+        to: new.version().clone(),
+        changes,
+    }
+}
+
+/// A runtime requirement that rejects runtimes it used to accept narrows where a manifest runs;
+/// one that only accepts more widens it. `host` names the runtime in the hint, such as "hub
+/// runtime".
+pub(super) fn runtime(old: &VersionReq, new: &VersionReq, host: &str, changes: &mut Vec<Change>) {
+    if old == new {
+        return;
+    }
+
+    let message = format!("runtime requirement changed from `{}` to `{}`", old, new);
+    let candidates = runtime_candidates(old, new);
+    if candidates.iter().any(|v| old.matches(v) && !new.matches(v)) {
+        changes.push(
+            Change::new(Impact::Major, "requires_runtime", message)
+                .with_hint(format!("needs a {} matching `{}`", host, new)),
+        );
+    } else if candidates.iter().any(|v| new.matches(v) && !old.matches(v)) {
+        changes.push(Change::new(Impact::Minor, "requires_runtime", message));
+    } else {
+        changes.push(Change::new(Impact::Patch, "requires_runtime", message));
+    }
+}
+
+/// Versions on either side of every bound in both requirements, which is where two
+/// requirements can disagree.
+fn runtime_candidates(old: &VersionReq, new: &VersionReq) -> Vec<Version> {
+    const ABOVE: u64 = u64::MAX / 2;
+    let mut candidates = vec![Version::new(0, 0, 0), Version::new(ABOVE, 0, 0)];
+
+    for comparator in old.comparators.iter().chain(&new.comparators) {
+        let (major, minor, patch) = (
+            comparator.major,
+            comparator.minor.unwrap_or(0),
+            comparator.patch.unwrap_or(0),
+        );
+        candidates.extend([
+            Version::new(major, minor, patch),
+            Version::new(major, minor, patch.saturating_add(1)),
+            Version::new(major, minor.saturating_add(1), 0),
+            Version::new(major.saturating_add(1), 0, 0),
+        ]);
+        if patch > 0 {
+            candidates.push(Version::new(major, minor, patch - 1));
+        }
+        if minor > 0 {
+            candidates.push(Version::new(major, minor - 1, ABOVE));
+        }
+        if major > 0 {
+            candidates.push(Version::new(major - 1, ABOVE, ABOVE));
+        }
+    }
+
+    candidates
+}
+
+/// Gaining a required capability narrows where a manifest runs; losing one widens it.
+pub(super) fn capabilities(old: &[ValidKey], new: &[ValidKey], changes: &mut Vec<Change>) {
+    for added in new.iter().filter(|key| !old.contains(key)) {
+        changes.push(
+            Change::new(
+                Impact::Major,
+                "requires_capabilities",
+                format!("requires the `{}` capability", added),
+            )
+            .with_hint(format!("drops support for runtimes without `{}`", added)),
+        );
+    }
+    for removed in old.iter().filter(|key| !new.contains(key)) {
+        changes.push(Change::new(
+            Impact::Minor,
+            "requires_capabilities",
+            format!("no longer requires the `{}` capability", removed),
+        ));
+    }
+}
+
+fn scenario(path: &str, old: &Scenario, new: &Scenario, changes: &mut Vec<Change>) {
+    let name = new.name();
+
+    if old.titles() != new.titles() || old.descriptions() != new.descriptions() {
+        changes.push(Change::new(
+            Impact::Patch,
+            path,
+            "titles or descriptions changed",
+        ));
+    }
+
+    properties(
+        &format!("{}.options", path),
+        old.options(),
+        new.options(),
+        Flow::Accepted,
+        changes,
+    );
+
+    if old.teams() != new.teams() || old.constraints() != new.constraints() {
+        changes.push(Change::new(
+            Impact::Major,
+            path,
+            "teams or player constraints changed",
+        ));
+    }
+
+    for removed in old
+        .players()
+        .iter()
+        .filter(|o| !new.players().iter().any(|n| n.name() == o.name()))
+    {
+        changes.push(
+            Change::new(
+                Impact::Major,
+                format!("{}.players.{}", path, removed.name()),
+                "player type removed",
+            )
+            .with_hint(format!(
+                "`{}` players can no longer join `{}`",
+                removed.name(),
+                name
+            )),
+        );
+    }
+
+    for player in new.players() {
+        let player_path = format!("{}.players.{}", path, player.name());
+        match old.players().iter().find(|o| o.name() == player.name()) {
+            None if player.count().contains(0) => {
+                changes.push(Change::new(Impact::Minor, player_path, "player type added"))
+            }
+            None => changes.push(
+                Change::new(Impact::Major, player_path, "required player type added").with_hint(
+                    format!(
+                        "`{}` now needs at least {} `{}` players",
+                        name,
+                        player.count().min,
+                        player.name()
+                    ),
+                ),
+            ),
+            Some(before) => self::player(&player_path, name, before, player, changes),
+        }
+    }
+}
+
+fn player(
+    path: &str,
+    scenario: &ValidKey,
+    old: &ScenarioPlayer,
+    new: &ScenarioPlayer,
+    changes: &mut Vec<Change>,
+) {
+    if old.titles() != new.titles() || old.descriptions() != new.descriptions() {
+        changes.push(Change::new(
+            Impact::Patch,
+            path,
+            "titles or descriptions changed",
+        ));
+    }
+
+    let (lost, gained) = counts(old.count(), new.count());
+    if !lost.is_empty() {
+        changes.push(
+            Change::new(
+                Impact::Major,
+                format!("{}.count", path),
+                format!("count narrowed from {} to {}", old.count(), new.count()),
+            )
+            .with_hint(format!(
+                "`{}` no longer supports {} `{}` players",
+                scenario,
+                list(&lost),
+                new.name()
+            )),
+        );
+    } else if !gained.is_empty() {
+        changes.push(Change::new(
+            Impact::Minor,
+            format!("{}.count", path),
+            format!("count widened from {} to {}", old.count(), new.count()),
+        ));
+    }
+
+    requirements(
+        &format!("{}.io", path),
+        scenario,
+        old.io(),
+        new.io(),
+        changes,
+    );
+    requirements(
+        &format!("{}.playables", path),
+        scenario,
+        old.playables(),
+        new.playables(),
+        changes,
+    );
+}
+
+fn requirements(
+    prefix: &str,
+    scenario: &ValidKey,
+    old: &[Requirement],
+    new: &[Requirement],
+    changes: &mut Vec<Change>,
+) {
+    for removed in old
+        .iter()
+        .filter(|o| !new.iter().any(|n| n.name() == o.name()))
+    {
+        changes.push(Change::new(
+            Impact::Minor,
+            format!("{}.{}", prefix, removed.name()),
+            "requirement removed",
+        ));
+    }
+
+    for requirement in new {
+        let path = format!("{}.{}", prefix, requirement.name());
+        let before = match old.iter().find(|o| o.name() == requirement.name()) {
+            Some(before) => before,
+            None => {
+                changes.push(
+                    Change::new(Impact::Major, path, "requirement added").with_hint(format!(
+                        "drops support for tables without a {} for `{}`",
+                        spec_name(requirement),
+                        scenario
+                    )),
+                );
+                continue;
+            }
+        };
+
+        if before.spec() != requirement.spec() || before.version() != requirement.version() {
+            changes.push(
+                Change::new(
+                    Impact::Major,
+                    path.clone(),
+                    format!(
+                        "now requires `{}` `{}` instead of `{}` `{}`",
+                        requirement.spec(),
+                        requirement.version(),
+                        before.spec(),
+                        before.version()
+                    ),
+                )
+                .with_hint(format!(
+                    "drops support for tables without a {} matching `{}` for `{}`",
+                    spec_name(requirement),
+                    requirement.version(),
+                    scenario
+                )),
+            );
+        }
+
+        if requirement.count() > before.count() {
+            changes.push(Change::new(
+                Impact::Major,
+                path.clone(),
+                format!(
+                    "needs {} slots instead of {}",
+                    requirement.count(),
+                    before.count()
+                ),
+            ));
+        } else if requirement.count() < before.count() {
+            changes.push(Change::new(
+                Impact::Minor,
+                path.clone(),
+                format!(
+                    "needs {} slots instead of {}",
+                    requirement.count(),
+                    before.count()
+                ),
+            ));
+        }
+
+        for feature in requirement
+            .features()
+            .iter()
+            .filter(|feature| !before.features().contains(feature))
+        {
+            changes.push(
+                Change::new(
+                    Impact::Major,
+                    path.clone(),
+                    format!("requires the `{}` feature", feature),
+                )
+                .with_hint(format!(
+                    "drops support for {}s without `{}` for `{}`",
+                    spec_name(requirement),
+                    feature,
+                    scenario
+                )),
+            );
+        }
+        for feature in before
+            .features()
+            .iter()
+            .filter(|feature| !requirement.features().contains(feature))
+        {
+            changes.push(Change::new(
+                Impact::Minor,
+                path.clone(),
+                format!("no longer requires the `{}` feature", feature),
+            ));
+        }
+
+        for predicate in requirement
+            .predicates()
+            .iter()
+            .filter(|predicate| !before.predicates().contains(predicate))
+        {
+            changes.push(Change::new(
+                Impact::Major,
+                path.clone(),
+                format!("requires `{}`", predicate),
+            ));
+        }
+        for predicate in before
+            .predicates()
+            .iter()
+            .filter(|predicate| !requirement.predicates().contains(predicate))
+        {
+            changes.push(Change::new(
+                Impact::Minor,
+                path.clone(),
+                format!("no longer requires `{}`", predicate),
+            ));
+        }
+    }
+}
+
+/// Counts only the old range allows, and counts only the new range allows.
+fn counts(old: &Range, new: &Range) -> (Vec<u8>, Vec<u8>) {
+    let lost = (0..=u8::MAX)
+        .filter(|count| old.contains(*count) && !new.contains(*count))
+        .collect();
+    let gained = (0..=u8::MAX)
+        .filter(|count| !old.contains(*count) && new.contains(*count))
+        .collect();
+
+    (lost, gained)
+}
+
+/// "4, 6, 8", with runs written as "41-50", or "41+" when they run to the largest count.
+fn list(counts: &[u8]) -> String {
+    let mut runs: Vec<(u8, u8)> = vec![];
+    for &count in counts {
+        match runs.last_mut() {
+            Some((_, last)) if count.checked_sub(1) == Some(*last) => *last = count,
+            _ => runs.push((count, count)),
+        }
+    }
+
+    let mut listed: Vec<String> = runs
+        .iter()
+        .take(LISTED_COUNTS)
+        .map(|&(first, last)| match last - first {
+            0 => first.to_string(),
+            _ if last == u8::MAX => format!("{}+", first),
+            1 => format!("{}, {}", first, last),
+            _ => format!("{}-{}", first, last),
+        })
+        .collect();
+    if runs.len() > LISTED_COUNTS {
+        listed.push("and more".to_string());
+    }
+    listed.join(", ")
+}
+
+/// "https://ravenfire.games/dev/specs/screen.json" and "ravenfire.screen" are a "screen".
+fn spec_name(requirement: &Requirement) -> String {
+    match requirement.spec() {
+        SpecIdentifier::Url(url) => {
+            let file = url.path().rsplit('/').next().unwrap_or_default();
+            file.trim_end_matches(".json").to_string()
+        }
+        SpecIdentifier::Key(key) => {
+            let key = key.to_string();
+            key.rsplit('.').next().unwrap_or_default().to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::data::serialization::Jsonable;
+    use common::semver::Version;
+
+    use crate::diff::game::diff;
+    use crate::diff::Impact;
+    use crate::examples::games::Game;
+    use crate::examples::Example;
+    use crate::game::{GameManifest, Requirement, ScenarioPlayer};
+
+    fn screen() -> Requirement {
+        Requirement::from_json(
+            r#"
+            {
+                "name": "board",
+                "spec": "https://ravenfire.games/dev/specs/screen.json",
+                "version": ">=10",
+                "count": 1
+            }
+            "#,
+        )
+        .expect("Failed to build requirement")
+    }
+
+    /// The game with the first scenario's champion changed.
+    fn with_champion(
+        game: &GameManifest,
+        change: impl FnOnce(&mut ScenarioPlayer),
+    ) -> GameManifest {
+        let mut game = game.clone();
+        let mut scenarios = game.scenarios().clone();
+        let mut players = scenarios[0].players().clone();
+        change(&mut players[0]);
+        scenarios[0].set_players(players);
+        game.set_scenarios(scenarios);
+        game
+    }
+
+    fn with_screen(game: &GameManifest) -> GameManifest {
+        let mut game = with_champion(game, |champion| {
+            let mut io = champion.io().clone();
+            io.push(screen());
+            champion.set_io(io);
+        });
+        game.set_version(Version::parse("10.0.0").unwrap());
+        game
+    }
+
+    #[test]
+    fn it_hints_at_new_hardware_requirements() {
+        let old = Game::simple_battle().build();
+        let new = with_screen(&old);
+
+        let diff = diff(&old, &new);
+
+        assert_eq!(diff.impact(), Some(Impact::Major));
+        assert_eq!(
+            diff.changes[0].path,
+            "scenarios.beginners.players.champion.io.board"
+        );
+        assert_eq!(
+            diff.changes[0].hint,
+            Some("drops support for tables without a screen for `beginners`".to_string())
+        );
+        assert_eq!(diff.check(), Ok(()));
+    }
+
+    #[test]
+    fn it_weighs_runtime_requirements_by_what_they_reject() {
+        let old = Game::simple_battle().build();
+        let runtime = |requirement: &str| {
+            let mut game = old.clone();
+            game.set_requires_runtime(requirement.parse().unwrap());
+            game
+        };
+        let change = |from: &str, to: &str| {
+            let diff = diff(&runtime(from), &runtime(to));
+            let change = &diff.changes[0];
+            (change.impact, change.hint.clone())
+        };
+
+        assert_eq!(
+            change(">=1.0", ">=1.2"),
+            (
+                Impact::Major,
+                Some("needs a runtime matching `>=1.2`".to_string())
+            )
+        );
+        assert_eq!(change(">=1.2", ">=1.0"), (Impact::Minor, None));
+        assert_eq!(change("^1.2", ">=1.2"), (Impact::Minor, None));
+        assert_eq!(change("^1.2", ">=1.2, <2"), (Impact::Patch, None));
+        assert_eq!(
+            change("^1.2", "^2"),
+            (
+                Impact::Major,
+                Some("needs a runtime matching `^2`".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn it_reports_narrowed_player_counts_and_removed_scenarios() {
+        let old = Game::simple_battle().build();
+        let mut new = with_champion(&old, |champion| {
+            champion.set_count("2".parse().unwrap());
+        });
+        let mut scenarios = new.scenarios().clone();
+        scenarios.pop();
+        new.set_scenarios(scenarios);
+
+        let diff = diff(&old, &new);
+
+        let changes: Vec<(&str, Impact)> = diff
+            .changes
+            .iter()
+            .map(|change| (change.path.as_str(), change.impact))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("scenarios.advanced", Impact::Major),
+                ("scenarios.beginners.players.champion.count", Impact::Major),
+            ]
+        );
+        assert_eq!(
+            diff.changes[1]
+                .hint
+                .as_deref()
+                .map(|hint| hint.starts_with("`beginners` no longer supports 4, 6, 8")),
+            Some(true)
+        );
+        assert!(diff.check().is_err());
+    }
+
+    #[test]
+    fn it_reports_counts_narrowed_above_32() {
+        for wider in ["2-50", "2+"] {
+            let old = with_champion(&Game::simple_battle().build(), |champion| {
+                champion.set_count(wider.parse().unwrap());
+            });
+            let new = with_champion(&old, |champion| {
+                champion.set_count("2-40".parse().unwrap());
+            });
+
+            let diff = diff(&old, &new);
+
+            assert_eq!(diff.impact(), Some(Impact::Major), "{}", wider);
+            assert_eq!(
+                diff.changes[0].hint.as_deref(),
+                Some(match wider {
+                    "2+" => "`beginners` no longer supports 41+ `champion` players",
+                    _ => "`beginners` no longer supports 41-50 `champion` players",
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn it_writes_a_changelog_and_json() {
+        let old = Game::simple_battle().build();
+        let new = with_screen(&old);
+
+        let diff = diff(&old, &new);
+        let changelog = diff.to_string();
+        let json: serde_json::Value =
+            serde_json::from_str(&diff.to_json().expect("Failed to serialize diff"))
+                .expect("Failed to read diff JSON");
+
+        assert!(changelog.starts_with("simple-battle 9.8.1 -> 10.0.0 (major changes)\n"));
+        assert!(changelog.contains("\nImpact:\n- drops support for tables without a screen"));
+        assert_eq!(json["changes"][0]["impact"], "major");
+        assert_eq!(
+            json["changes"][0]["hint"],
+            "drops support for tables without a screen for `beginners`"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Diffs between two releases of a `PeripheralManifest`.
+//!
+//! A peripheral release is major when a game that matched it before might not anymore: a
+//! provider, slot, or feature is gone, or its spec moved to a version requirements may not match.
+use crate::diff::game::{capabilities, runtime};
+use crate::diff::{spec, Change, Diff, Impact};
+use crate::peripheral::{PeripheralManifest, Provider};
+
+/// Every change between two releases of a peripheral.
+pub fn diff(old: &PeripheralManifest, new: &PeripheralManifest) -> Diff {
+    let mut changes = vec![];
+
+    if old.vendor() != new.vendor() {
+        changes.push(Change::new(
+            Impact::Patch,
+            "vendor",
+            "vendor contact changed",
+        ));
+    }
+    if old.titles() != new.titles() || old.descriptions() != new.descriptions() {
+        changes.push(Change::new(
+            Impact::Patch,
+            "titles",
+            "titles or descriptions changed",
+        ));
+    }
+    if old.url() != new.url() || old.support() != new.support() {
+        changes.push(Change::new(Impact::Patch, "url", "links changed"));
+    }
+    if old.layout() != new.layout() {
+        changes.push(Change::new(Impact::Patch, "layout", "layout changed"));
+    }
+
+    runtime(
+        old.requires_runtime(),
+        new.requires_runtime(),
+        "hub runtime",
+        &mut changes,
+    );
+    capabilities(
+        old.requires_capabilities(),
+        new.requires_capabilities(),
+        &mut changes,
+    );
+
+    for removed in old
+        .connections()
+        .iter()
+        .filter(|connection| !new.connections().contains(connection))
+    {
+        changes.push(
+            Change::new(Impact::Major, "connections", "connection removed").with_hint(format!(
+                "hubs can no longer reach the device over {}",
+                serde_json::to_value(removed).unwrap_or_default()["type"]
+                    .as_str()
+                    .unwrap_or("a removed connection")
+            )),
+        );
+    }
+    if new
+        .connections()
+        .iter()
+        .any(|connection| !old.connections().contains(connection))
+    {
+        changes.push(Change::new(
+            Impact::Minor,
+            "connections",
+            "connection added",
+        ));
+    }
+
+    for removed in old
+        .provides()
+        .iter()
+        .filter(|o| !new.provides().iter().any(|n| n.name() == o.name()))
+    {
+        changes.push(
+            Change::new(
+                Impact::Major,
+                format!("provides.{}", removed.name()),
+                "provider removed",
+            )
+            .with_hint(format!(
+                "no longer provides `{}` for games",
+                removed.spec().url()
+            )),
+        );
+    }
+
+    for provider in new.provides() {
+        let path = format!("provides.{}", provider.name());
+        match old.provides().iter().find(|o| o.name() == provider.name()) {
+            None => changes.push(Change::new(Impact::Minor, path, "provider added")),
+            Some(before) => self::provider(&path, before, provider, &mut changes),
+        }
+    }
+
+    Diff {
+        subject: new.key().to_string(),
+        from: old.version().clone(),
+        to: new.version().clone(),
+        changes,
+    }
+}
+
+fn provider(path: &str, old: &Provider, new: &Provider, changes: &mut Vec<Change>) {
+    if new.count() < old.count() {
+        changes.push(
+            Change::new(
+                Impact::Major,
+                path,
+                format!("has {} slots instead of {}", new.count(), old.count()),
+            )
+            .with_hint(format!(
+                "games needing more than {} `{}` slots no longer fit",
+                new.count(),
+                new.name()
+            )),
+        );
+    } else if new.count() > old.count() {
+        changes.push(Change::new(
+            Impact::Minor,
+            path,
+            format!("has {} slots instead of {}", new.count(), old.count()),
+        ));
+    }
+
+    // A new spec version may fall outside of requirements written against the old one
+    let spec_diff = spec::diff(old.spec(), new.spec());
+    if old.spec().version() != new.spec().version() {
+        changes.push(Change::new(
+            spec_diff.declared().unwrap_or(Impact::Major),
+            format!("{}.spec", path),
+            format!(
+                "implements version {} instead of {}",
+                new.spec().version(),
+                old.spec().version()
+            ),
+        ));
+    }
+    changes.extend(spec_diff.changes.into_iter().map(|change| Change {
+        path: format!("{}.spec.{}", path, change.path),
+        ..change
+    }));
+
+    for slot in 0..*old.count().min(new.count()) {
+        let before = old.features_for(slot);
+        let after = new.features_for(slot);
+
+        for lost in before.iter().filter(|feature| !after.contains(feature)) {
+            changes.push(Change::new(
+                Impact::Major,
+                format!("{}.slots.{}", path, slot),
+                format!("no longer implements `{}`", lost),
+            ));
+        }
+        for gained in after.iter().filter(|feature| !before.contains(feature)) {
+            changes.push(Change::new(
+                Impact::Minor,
+                format!("{}.slots.{}", path, slot),
+                format!("now implements `{}`", gained),
+            ));
+        }
+    }
+
+    for (key, value) in old.values().values() {
+        match new.values().get(key) {
+            Some(after) if after == value => {}
+            Some(after) => changes.push(Change::new(
+                Impact::Major,
+                format!("{}.values.{}", path, key),
+                format!("changed from {} to {}", value, after),
+            )),
+            None => changes.push(Change::new(
+                Impact::Major,
+                format!("{}.values.{}", path, key),
+                "value removed",
+            )),
+        }
+    }
+    for key in new.values().values().keys() {
+        if old.values().get(key).is_none() {
+            changes.push(Change::new(
+                Impact::Minor,
+                format!("{}.values.{}", path, key),
+                "value added",
+            ));
+        }
+    }
+
+    if old.positions() != new.positions() {
+        changes.push(Change::new(
+            Impact::Patch,
+            format!("{}.positions", path),
+            "slot positions changed",
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::semver::Version;
+
+    use crate::diff::peripheral::diff;
+    use crate::diff::Impact;
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::Example;
+
+    #[test]
+    fn it_reports_provider_and_connection_changes() {
+        let old = Peripheral::rf_card_reader().build();
+        let mut new = old.clone();
+        new.set_version(Version::parse("2.19.0").unwrap());
+        new.set_connections(old.connections()[..1].to_vec());
+        let mut provides = new.provides().clone();
+        provides[0].set_count(6);
+        new.set_provides(provides);
+
+        let diff = diff(&old, &new);
+
+        let changes: Vec<(&str, Impact)> = diff
+            .changes
+            .iter()
+            .map(|change| (change.path.as_str(), change.impact))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("connections", Impact::Major),
+                ("provides.peripheral_defined_card_reader", Impact::Minor),
+            ]
+        );
+        assert_eq!(
+            diff.changes[0].hint,
+            Some("hubs can no longer reach the device over ble".to_string())
+        );
+        assert!(diff.check().is_err());
+    }
+
+    #[test]
+    fn it_weighs_spec_version_changes_by_their_bump() {
+        let old = Peripheral::rf_card_reader().build();
+        let mut new = old.clone();
+        let mut provides = new.provides().clone();
+        let mut spec = provides[0].spec().clone();
+        spec.set_version(Version::parse("2.0.0").unwrap());
+        provides[0].set_spec(spec);
+        new.set_provides(provides);
+
+        let diff = diff(&old, &new);
+
+        assert_eq!(
+            diff.changes[0].path,
+            "provides.peripheral_defined_card_reader.spec"
+        );
+        assert_eq!(diff.impact(), Some(Impact::Major));
+    }
+}
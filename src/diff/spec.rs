@@ -0,0 +1,580 @@
+//! Diffs between two releases of a `Spec`.
+//!
+//! Games read state and events, and send commands. So a device reporting something a game did
+//! not expect is breaking, and so is a device refusing something a game used to send: widening
+//! a reported enumeration is major, while widening a command parameter's is minor.
+use common::data::schema::Property;
+
+use crate::deprecation::{DeprecatedProperty, Deprecation};
+use crate::diff::{Change, Diff, Impact};
+use crate::introspect::PropertyShape;
+use crate::specs::{Command, Event, Feature, Spec};
+
+/// Which way values flow through a property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Flow {
+    /// State and event payloads, from the device to the game
+    Reported,
+    /// Command parameters, from the game to the device
+    Accepted,
+}
+
+/// Every change between two releases of a spec.
+pub fn diff(old: &Spec, new: &Spec) -> Diff {
+    let mut changes = vec![];
+
+    if old.key() != new.key() {
+        changes.push(Change::new(
+            Impact::Major,
+            "key",
+            format!("renamed from `{}` to `{}`", old.key(), new.key()),
+        ));
+    }
+    // The key keeps the spec's identity, but requirements written against the old URL need an
+    // alias to keep matching
+    if old.url() != new.url() {
+        changes.push(
+            Change::new(
+                Impact::Minor,
+                "url",
+                format!("moved from `{}` to `{}`", old.url(), new.url()),
+            )
+            .with_hint(format!(
+                "requirements on `{}` need a registry alias for `{}`",
+                old.url(),
+                new.key()
+            )),
+        );
+    }
+    if old.extends() != new.extends() {
+        changes.push(Change::new(
+            Impact::Major,
+            "extends",
+            "the extended spec changed",
+        ));
+    }
+    if old.vendor() != new.vendor() {
+        changes.push(Change::new(
+            Impact::Patch,
+            "vendor",
+            "vendor details changed",
+        ));
+    }
+    if old.titles() != new.titles() || old.descriptions() != new.descriptions() {
+        changes.push(Change::new(
+            Impact::Patch,
+            "titles",
+            "titles or descriptions changed",
+        ));
+    }
+
+    deprecation(
+        "spec",
+        old.deprecated().as_ref(),
+        new.deprecated().as_ref(),
+        &mut changes,
+    );
+    deprecated_properties(
+        "state",
+        old.deprecated_properties(),
+        new.deprecated_properties(),
+        &mut changes,
+    );
+
+    properties(
+        "state",
+        old.state(),
+        new.state(),
+        Flow::Reported,
+        &mut changes,
+    );
+    events("events", old.events(), new.events(), &mut changes);
+    commands("commands", old.commands(), new.commands(), &mut changes);
+    features(old.features(), new.features(), &mut changes);
+
+    Diff {
+        subject: new.url().to_string(),
+        from: old.version().clone(),
+        to: new.version().clone(),
+        changes,
+    }
+}
+
+fn features(old: &[Feature], new: &[Feature], changes: &mut Vec<Change>) {
+    for removed in old
+        .iter()
+        .filter(|o| !new.iter().any(|n| n.key() == o.key()))
+    {
+        changes.push(Change::new(
+            Impact::Major,
+            format!("features.{}", removed.key()),
+            "feature removed",
+        ));
+    }
+
+    for feature in new {
+        let path = format!("features.{}", feature.key());
+        let before = match old.iter().find(|o| o.key() == feature.key()) {
+            Some(before) => before,
+            None => {
+                changes.push(Change::new(Impact::Minor, path, "feature added"));
+                continue;
+            }
+        };
+
+        if before.titles() != feature.titles() || before.descriptions() != feature.descriptions() {
+            changes.push(Change::new(
+                Impact::Patch,
+                path.clone(),
+                "titles or descriptions changed",
+            ));
+        }
+        deprecation(
+            &path,
+            before.deprecated().as_ref(),
+            feature.deprecated().as_ref(),
+            changes,
+        );
+        deprecated_properties(
+            &format!("{}.state", path),
+            before.deprecated_properties(),
+            feature.deprecated_properties(),
+            changes,
+        );
+        properties(
+            &format!("{}.state", path),
+            before.state(),
+            feature.state(),
+            Flow::Reported,
+            changes,
+        );
+        events(
+            &format!("{}.events", path),
+            before.events(),
+            feature.events(),
+            changes,
+        );
+        commands(
+            &format!("{}.commands", path),
+            before.commands(),
+            feature.commands(),
+            changes,
+        );
+    }
+}
+
+/// Deprecating something is an addition: everything keeps working until it is removed.
+fn deprecation(
+    path: &str,
+    old: Option<&Deprecation>,
+    new: Option<&Deprecation>,
+    changes: &mut Vec<Change>,
+) {
+    match (old, new) {
+        (None, Some(deprecation)) => changes.push(
+            Change::new(Impact::Minor, path, "deprecated").with_hint(deprecation.warning(
+                &format!("`{}`", path),
+                "en",
+                None,
+            )),
+        ),
+        (Some(_), None) => changes.push(Change::new(Impact::Patch, path, "no longer deprecated")),
+        (Some(before), Some(after)) if before != after => changes.push(Change::new(
+            Impact::Patch,
+            path,
+            "deprecation details changed",
+        )),
+        _ => {}
+    }
+}
+
+fn deprecated_properties(
+    prefix: &str,
+    old: &[DeprecatedProperty],
+    new: &[DeprecatedProperty],
+    changes: &mut Vec<Change>,
+) {
+    for property in new {
+        let before = old.iter().find(|o| o.key() == property.key());
+        deprecation(
+            &format!("{}.{}", prefix, property.key()),
+            before.map(|before| before.deprecation()),
+            Some(property.deprecation()),
+            changes,
+        );
+    }
+    for removed in old
+        .iter()
+        .filter(|o| !new.iter().any(|n| n.key() == o.key()))
+    {
+        changes.push(Change::new(
+            Impact::Patch,
+            format!("{}.{}", prefix, removed.key()),
+            "no longer deprecated",
+        ));
+    }
+}
+
+fn events(prefix: &str, old: &[Event], new: &[Event], changes: &mut Vec<Change>) {
+    for removed in old
+        .iter()
+        .filter(|o| !new.iter().any(|n| n.key() == o.key()))
+    {
+        changes.push(Change::new(
+            Impact::Major,
+            format!("{}.{}", prefix, removed.key()),
+            "event removed",
+        ));
+    }
+
+    for event in new {
+        let path = format!("{}.{}", prefix, event.key());
+        match old.iter().find(|o| o.key() == event.key()) {
+            None => changes.push(Change::new(Impact::Minor, path, "event added")),
+            Some(before) => {
+                if before.titles() != event.titles()
+                    || before.descriptions() != event.descriptions()
+                {
+                    changes.push(Change::new(
+                        Impact::Patch,
+                        path.clone(),
+                        "titles or descriptions changed",
+                    ));
+                }
+                properties(
+                    &format!("{}.payload", path),
+                    before.payload(),
+                    event.payload(),
+                    Flow::Reported,
+                    changes,
+                );
+            }
+        }
+    }
+}
+
+fn commands(prefix: &str, old: &[Command], new: &[Command], changes: &mut Vec<Change>) {
+    for removed in old
+        .iter()
+        .filter(|o| !new.iter().any(|n| n.key() == o.key()))
+    {
+        changes.push(Change::new(
+            Impact::Major,
+            format!("{}.{}", prefix, removed.key()),
+            "command removed",
+        ));
+    }
+
+    for command in new {
+        let path = format!("{}.{}", prefix, command.key());
+        match old.iter().find(|o| o.key() == command.key()) {
+            None => changes.push(Change::new(Impact::Minor, path, "command added")),
+            Some(before) => {
+                if before.titles() != command.titles()
+                    || before.descriptions() != command.descriptions()
+                {
+                    changes.push(Change::new(
+                        Impact::Patch,
+                        path.clone(),
+                        "titles or descriptions changed",
+                    ));
+                }
+                properties(
+                    &format!("{}.parameters", path),
+                    before.parameters(),
+                    command.parameters(),
+                    Flow::Accepted,
+                    changes,
+                );
+            }
+        }
+    }
+}
+
+pub(super) fn properties(
+    prefix: &str,
+    old: &[Property],
+    new: &[Property],
+    flow: Flow,
+    changes: &mut Vec<Change>,
+) {
+    let old: Vec<PropertyShape> = old.iter().map(PropertyShape::of).collect();
+    let new: Vec<PropertyShape> = new.iter().map(PropertyShape::of).collect();
+
+    for removed in old.iter().filter(|o| !new.iter().any(|n| n.key == o.key)) {
+        changes.push(Change::new(
+            Impact::Major,
+            format!("{}.{}", prefix, removed.key),
+            "property removed",
+        ));
+    }
+
+    for shape in &new {
+        let path = format!("{}.{}", prefix, shape.key);
+        match old.iter().find(|o| o.key == shape.key) {
+            None => {
+                // Games that do not know about a new parameter will not send it
+                let impact = if flow == Flow::Accepted && !shape.optional {
+                    Impact::Major
+                } else {
+                    Impact::Minor
+                };
+                let kind = if shape.optional {
+                    "optional"
+                } else {
+                    "required"
+                };
+                changes.push(Change::new(
+                    impact,
+                    path,
+                    format!("{} property added", kind),
+                ));
+            }
+            Some(before) => property(&path, before, shape, flow, changes),
+        }
+    }
+}
+
+fn property(
+    path: &str,
+    old: &PropertyShape,
+    new: &PropertyShape,
+    flow: Flow,
+    changes: &mut Vec<Change>,
+) {
+    if old.data_type != new.data_type {
+        changes.push(Change::new(
+            Impact::Major,
+            path,
+            format!(
+                "data type changed from {} to {}",
+                old.data_type.name(),
+                new.data_type.name()
+            ),
+        ));
+    }
+    if old.collection != new.collection {
+        let description = if new.collection {
+            "became a collection"
+        } else {
+            "is no longer a collection"
+        };
+        changes.push(Change::new(Impact::Major, path, description));
+    }
+
+    if old.optional != new.optional {
+        // Games can no longer count on a reported value, or must now send a parameter
+        let loosened = new.optional;
+        let breaking = match flow {
+            Flow::Reported => loosened,
+            Flow::Accepted => !loosened,
+        };
+        changes.push(Change::new(
+            if breaking {
+                Impact::Major
+            } else {
+                Impact::Minor
+            },
+            path,
+            if loosened {
+                "became optional"
+            } else {
+                "became required"
+            },
+        ));
+    }
+
+    enumerations(path, old, new, flow, changes);
+
+    if old.titles != new.titles || old.descriptions != new.descriptions {
+        changes.push(Change::new(
+            Impact::Patch,
+            path,
+            "titles or descriptions changed",
+        ));
+    }
+}
+
+/// No enumerations means any value is allowed.
+fn enumerations(
+    path: &str,
+    old: &PropertyShape,
+    new: &PropertyShape,
+    flow: Flow,
+    changes: &mut Vec<Change>,
+) {
+    let removed: Vec<String> = old
+        .enumerations
+        .iter()
+        .filter(|value| !new.enumerations.is_empty() && !new.enumerations.contains(value))
+        .map(|value| value.to_string())
+        .collect();
+    let added: Vec<String> = new
+        .enumerations
+        .iter()
+        .filter(|value| !old.enumerations.is_empty() && !old.enumerations.contains(value))
+        .map(|value| value.to_string())
+        .collect();
+    let constrained = old.enumerations.is_empty() && !new.enumerations.is_empty();
+    let unconstrained = !old.enumerations.is_empty() && new.enumerations.is_empty();
+
+    // Widening breaks games that read the value, narrowing breaks games that send it
+    let (widened, narrowed) = match flow {
+        Flow::Reported => (Impact::Major, Impact::Minor),
+        Flow::Accepted => (Impact::Minor, Impact::Major),
+    };
+
+    if !added.is_empty() {
+        changes.push(Change::new(
+            widened,
+            path,
+            format!("enumerations widened with {}", added.join(", ")),
+        ));
+    }
+    if unconstrained {
+        changes.push(Change::new(widened, path, "enumerations removed"));
+    }
+    if !removed.is_empty() {
+        changes.push(Change::new(
+            narrowed,
+            path,
+            format!("enumerations narrowed, dropping {}", removed.join(", ")),
+        ));
+    }
+    if constrained {
+        changes.push(Change::new(narrowed, path, "enumerations added"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::data::schema::Property;
+    use common::data::serialization::Jsonable;
+    use common::semver::Version;
+
+    use crate::diff::spec::diff;
+    use crate::diff::Impact;
+    use crate::examples::specs::Spec;
+    use crate::examples::Example;
+
+    fn property(json: &str) -> Property {
+        Property::from_json(json).expect("Failed to build property")
+    }
+
+    #[test]
+    fn it_finds_nothing_between_identical_specs() {
+        let spec = Spec::card().build();
+
+        let diff = diff(&spec, &spec);
+
+        assert_eq!(diff.changes, vec![]);
+        assert_eq!(diff.check(), Ok(()));
+    }
+
+    #[test]
+    fn it_classifies_additions_as_minor() {
+        let old = Spec::card().build();
+        let mut new = old.clone();
+        new.set_version(Version::parse("1.3.0").unwrap());
+        let mut state = new.state().clone();
+        state.push(property(
+            r#"{ "key": "holder", "data_type": "String", "optional": true }"#,
+        ));
+        new.set_state(state);
+
+        let diff = diff(&old, &new);
+
+        assert_eq!(diff.impact(), Some(Impact::Minor));
+        assert_eq!(diff.changes[0].path, "state.holder");
+        assert_eq!(diff.check(), Ok(()));
+    }
+
+    #[test]
+    fn it_fails_breaking_changes_shipped_as_minor_releases() {
+        let old = Spec::card().build();
+        let mut new = old.clone();
+        new.set_version(Version::parse("1.3.0").unwrap());
+        let mut features = new.features().clone();
+        features.retain(|feature| feature.key().to_string() != "nfc");
+        new.set_features(features);
+
+        let diff = diff(&old, &new);
+
+        assert_eq!(diff.impact(), Some(Impact::Major));
+        assert_eq!(
+            diff.check(),
+            Err(vec![
+                "`features.nfc`: feature removed needs a major release, but 1.2.3 to 1.3.0 is a minor release"
+                    .to_string()
+            ])
+        );
+
+        new.set_version(Version::parse("2.0.0").unwrap());
+        assert_eq!(super::diff(&old, &new).check(), Ok(()));
+    }
+
+    #[test]
+    fn it_weighs_enumerations_by_direction() {
+        let old = Spec::card().build();
+        let mut new = old.clone();
+        new.set_version(Version::parse("1.2.4").unwrap());
+        let mut features = new.features().clone();
+        for feature in features.iter_mut() {
+            if feature.key().to_string() == "facing" {
+                feature.set_state(vec![property(
+                    r#"{ "key": "facing", "data_type": "String", "enumerations": [{ "String": "up" }, { "String": "down" }, { "String": "edge" }] }"#,
+                )]);
+            }
+            if feature.key().to_string() == "indicator" {
+                let mut commands = feature.commands().clone();
+                commands[0].set_parameters(vec![property(
+                    r#"{ "key": "color", "data_type": "String", "enumerations": [{ "String": "off" }, { "String": "red" }] }"#,
+                )]);
+                feature.set_commands(commands);
+            }
+        }
+        new.set_features(features);
+
+        let impacts: Vec<(String, Impact)> = diff(&old, &new)
+            .changes
+            .into_iter()
+            .filter(|change| change.description.starts_with("enumerations"))
+            .map(|change| (change.path, change.impact))
+            .collect();
+
+        assert_eq!(
+            impacts,
+            vec![
+                ("features.facing.state.facing".to_string(), Impact::Major),
+                (
+                    "features.indicator.commands.set_indicator.parameters.color".to_string(),
+                    Impact::Major
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_treats_pre_1_0_minor_releases_as_breaking() {
+        let mut old = Spec::min().build();
+        old.set_version(Version::parse("0.1.0").unwrap());
+        let mut new = old.clone();
+        new.set_version(Version::parse("0.2.0").unwrap());
+
+        assert_eq!(diff(&old, &new).declared(), Some(Impact::Major));
+        new.set_version(Version::parse("0.1.1").unwrap());
+        assert_eq!(diff(&old, &new).declared(), Some(Impact::Minor));
+    }
+
+    #[test]
+    fn it_treats_every_0_0_x_release_as_breaking() {
+        let mut old = Spec::min().build();
+        old.set_version(Version::parse("0.0.1").unwrap());
+        let mut new = old.clone();
+        new.set_version(Version::parse("0.0.2").unwrap());
+
+        assert_eq!(diff(&old, &new).declared(), Some(Impact::Major));
+        new.set_version(Version::parse("0.1.0").unwrap());
+        assert_eq!(diff(&old, &new).declared(), Some(Impact::Major));
+    }
+}
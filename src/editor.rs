@@ -0,0 +1,437 @@
+//! Edits to hand-written TOML manifests that keep the author's comments and layout.
+//!
+//! Deserializing a manifest and writing it back out loses everything serde does not model.
+//! An `Editor` changes the TOML document itself, touching only the keys an edit is about, and
+//! checks after every edit that the document still deserializes and validates. An edit that
+//! would break the manifest is undone and reported instead.
+//!
+//! New tables, such as an added scenario, are written the way `annotated` writes them.
+use std::fmt;
+
+use serde::Serialize;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+use common::data::serialization::Tomlable;
+use common::semver::Version;
+
+use crate::annotated;
+use crate::game::{GameManifest, Requirement, Scenario};
+use crate::peripheral::{PeripheralManifest, Provider};
+use crate::Manifest;
+
+/// Language new tables are commented in, when they have it.
+const COMMENT_LOCALE: &str = "en";
+
+/// A TOML manifest being edited, and what it deserializes to.
+pub struct Editor<T> {
+    document: DocumentMut,
+    manifest: T,
+}
+
+/// A table in a list of them, written either as `[[name]]` or inline.
+struct Entry<'a> {
+    table: &'a mut dyn TableLike,
+    inline: bool,
+}
+
+impl<T: Tomlable<Entity = T> + Manifest> Editor<T> {
+    pub fn parse(toml: &str) -> Result<Self, Vec<String>> {
+        let document: DocumentMut = toml.parse().map_err(|error| vec![format!("{}", error)])?;
+        let manifest = Self::deserialize(&document)?;
+
+        Ok(Self { document, manifest })
+    }
+
+    /// The manifest as of the last successful edit.
+    pub fn manifest(&self) -> &T {
+        &self.manifest
+    }
+
+    pub fn set_version(&mut self, version: &Version) -> Result<(), Vec<String>> {
+        self.edit(|document| {
+            replace(
+                document.as_table_mut(),
+                "version",
+                version.to_string().into(),
+            );
+            Ok(())
+        })
+    }
+
+    /// Sets the manifest's title in one language, leaving the others alone.
+    pub fn set_title(&mut self, locale: &str, title: &str) -> Result<(), Vec<String>> {
+        self.edit(|document| {
+            localize(document.as_table_mut(), "titles", locale, title);
+            Ok(())
+        })
+    }
+
+    /// Sets the manifest's description in one language, leaving the others alone.
+    pub fn set_description(&mut self, locale: &str, description: &str) -> Result<(), Vec<String>> {
+        self.edit(|document| {
+            localize(document.as_table_mut(), "descriptions", locale, description);
+            Ok(())
+        })
+    }
+
+    /// Applies an edit to a copy of the document, and keeps it only if the manifest it
+    /// deserializes to is still valid.
+    fn edit<F>(&mut self, edit: F) -> Result<(), Vec<String>>
+    where
+        F: FnOnce(&mut DocumentMut) -> Result<(), String>,
+    {
+        let mut document = self.document.clone();
+        edit(&mut document).map_err(|error| vec![error])?;
+        self.manifest = Self::deserialize(&document)?;
+        self.document = document;
+
+        Ok(())
+    }
+
+    fn deserialize(document: &DocumentMut) -> Result<T, Vec<String>> {
+        let manifest =
+            T::from_toml(&document.to_string()).map_err(|error| vec![format!("{:?}", error)])?;
+        manifest.validate()?;
+
+        Ok(manifest)
+    }
+}
+
+impl Editor<GameManifest> {
+    pub fn add_scenario(&mut self, scenario: &Scenario) -> Result<(), Vec<String>> {
+        let table = new_table(scenario, "scenarios")?;
+        self.edit(|document| {
+            append(
+                Entry {
+                    table: document.as_table_mut(),
+                    inline: false,
+                },
+                "scenarios",
+                table,
+            )
+        })
+    }
+
+    /// Adds a requirement to the `io` of a player type in a scenario.
+    pub fn add_requirement(
+        &mut self,
+        scenario: &str,
+        player: &str,
+        requirement: &Requirement,
+    ) -> Result<(), Vec<String>> {
+        let table = new_table(requirement, "scenarios.players.io")?;
+        self.edit(|document| {
+            let scenario = find(document.as_table_mut(), "scenarios", scenario)
+                .ok_or_else(|| format!("no scenario `{}`", scenario))?;
+            let player = find(scenario.table, "players", player)
+                .ok_or_else(|| format!("no player type `{}`", player))?;
+
+            append(player, "io", table)
+        })
+    }
+
+    /// Sets a scenario's title in one language, leaving the others alone.
+    pub fn set_scenario_title(
+        &mut self,
+        scenario: &str,
+        locale: &str,
+        title: &str,
+    ) -> Result<(), Vec<String>> {
+        self.edit(|document| {
+            let entry = find(document.as_table_mut(), "scenarios", scenario)
+                .ok_or_else(|| format!("no scenario `{}`", scenario))?;
+
+            localize(entry.table, "titles", locale, title);
+            Ok(())
+        })
+    }
+}
+
+impl Editor<PeripheralManifest> {
+    pub fn add_provider(&mut self, provider: &Provider) -> Result<(), Vec<String>> {
+        let table = new_table(provider, "provides")?;
+        self.edit(|document| {
+            append(
+                Entry {
+                    table: document.as_table_mut(),
+                    inline: false,
+                },
+                "provides",
+                table,
+            )
+        })
+    }
+
+    /// Removes a provider by name, along with the comments above it.
+    pub fn remove_provider(&mut self, name: &str) -> Result<(), Vec<String>> {
+        self.edit(|document| {
+            let missing = || format!("no provider `{}`", name);
+            let named =
+                |table: &dyn TableLike| table.get("name").and_then(Item::as_str) == Some(name);
+
+            match document.get_mut("provides") {
+                Some(Item::ArrayOfTables(tables)) => {
+                    let index = tables
+                        .iter()
+                        .position(|table| named(table))
+                        .ok_or_else(missing)?;
+                    tables.remove(index);
+                }
+                Some(Item::Value(Value::Array(array))) => {
+                    let index = array
+                        .iter()
+                        .position(|value| value.as_inline_table().is_some_and(|table| named(table)))
+                        .ok_or_else(missing)?;
+                    array.remove(index);
+                }
+                _ => return Err(missing()),
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<T> fmt::Display for Editor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.document)
+    }
+}
+
+fn new_table(value: &impl Serialize, path: &str) -> Result<Table, Vec<String>> {
+    annotated::table(value, path, COMMENT_LOCALE).map_err(|error| vec![error.to_string()])
+}
+
+/// Replaces a value, keeping the comments and spacing around it.
+fn replace(table: &mut dyn TableLike, key: &str, mut value: Value) {
+    match table.get_mut(key).and_then(Item::as_value_mut) {
+        Some(existing) => {
+            *value.decor_mut() = existing.decor().clone();
+            *existing = value;
+        }
+        None => {
+            table.insert(key, Item::Value(value));
+        }
+    }
+}
+
+/// Sets one language in a language map, whether it is written inline, dotted, or as a table.
+fn localize(table: &mut dyn TableLike, field: &str, locale: &str, text: &str) {
+    match table.get_mut(field) {
+        // The space before the closing brace belongs to the last value, so it moves to the new one
+        Some(Item::Value(Value::InlineTable(map))) if !map.contains_key(locale) => {
+            let mut value: Value = text.into();
+            if let Some((_, last)) = map.iter_mut().last() {
+                if let Some(suffix) = last.decor().suffix().cloned() {
+                    value.decor_mut().set_suffix(suffix);
+                }
+                last.decor_mut().set_suffix("");
+            }
+            map.insert(locale, value);
+        }
+        Some(item) => {
+            if let Some(map) = item.as_table_like_mut() {
+                replace(map, locale, text.into());
+            }
+        }
+        None => {
+            let mut map = InlineTable::new();
+            map.insert(locale, text.into());
+            map.fmt();
+            table.insert(field, Item::Value(Value::InlineTable(map)));
+        }
+    }
+}
+
+/// The table in the list at `key` whose `name` is `name`.
+fn find<'a>(table: &'a mut dyn TableLike, key: &str, name: &str) -> Option<Entry<'a>> {
+    let named = |table: &dyn TableLike| table.get("name").and_then(Item::as_str) == Some(name);
+
+    match table.get_mut(key)? {
+        Item::ArrayOfTables(tables) => {
+            tables
+                .iter_mut()
+                .find(|table| named(*table))
+                .map(|table| Entry {
+                    table,
+                    inline: false,
+                })
+        }
+        Item::Value(Value::Array(array)) => array
+            .iter_mut()
+            .filter_map(Value::as_inline_table_mut)
+            .find(|table| named(*table))
+            .map(|table| Entry {
+                table,
+                inline: true,
+            }),
+        _ => None,
+    }
+}
+
+/// Adds a table to the list at `key`, as `[[key]]` unless the list is already written inline.
+fn append(parent: Entry, key: &str, table: Table) -> Result<(), String> {
+    let empty = match parent.table.get(key) {
+        None => true,
+        Some(Item::Value(Value::Array(array))) => array.is_empty(),
+        Some(_) => false,
+    };
+    if empty {
+        let list = if parent.inline {
+            Item::Value(Value::Array(Array::new()))
+        } else {
+            Item::ArrayOfTables(ArrayOfTables::new())
+        };
+        match parent.table.get_mut(key) {
+            Some(existing) => *existing = list,
+            None => {
+                parent.table.insert(key, list);
+            }
+        }
+    }
+
+    match parent.table.get_mut(key) {
+        Some(Item::ArrayOfTables(tables)) => tables.push(table),
+        Some(Item::Value(Value::Array(array))) => {
+            let mut inline = table.into_inline_table();
+            inline.fmt();
+            array.push(inline);
+        }
+        _ => return Err(format!("`{}` is not a list of tables", key)),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use common::data::serialization::Tomlable;
+    use common::semver::Version;
+
+    use crate::annotated;
+    use crate::editor::Editor;
+    use crate::examples::games::Game;
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::Example;
+    use crate::game::{GameManifest, Requirement};
+    use crate::peripheral::PeripheralManifest;
+
+    const HAND_WRITTEN: &str = r#"# Our first game. Keep this comment!
+key = "min-game"
+version = "9.8.1" # bumped by the release script
+titles = { en = "Min" } # more languages soon
+
+[vendor] # the studio
+name = "ravenfire"
+
+# The only way to play, for now
+[[scenarios]]
+name = "solo"
+players = [ { name = "hero", count = "1", io = [] } ]
+"#;
+
+    #[test]
+    fn it_keeps_comments_through_edits() {
+        let mut editor: Editor<GameManifest> = Editor::parse(HAND_WRITTEN).unwrap();
+
+        editor.set_version(&Version::new(9, 9, 0)).unwrap();
+        editor.set_title("es", "Mínimo").unwrap();
+        editor.set_scenario_title("solo", "en", "Solo").unwrap();
+
+        let requirement: Requirement = serde_json::from_str(
+            r#"{ "name": "hand", "spec": "ravenfire.card", "version": "^1.2", "count": 5 }"#,
+        )
+        .unwrap();
+        editor
+            .add_requirement("solo", "hero", &requirement)
+            .unwrap();
+
+        let scenario = Game::simple_battle().build().scenarios()[0].clone();
+        editor.add_scenario(&scenario).unwrap();
+
+        let toml = editor.to_string();
+        assert!(toml.starts_with("# Our first game. Keep this comment!\n"));
+        assert!(toml.contains("version = \"9.9.0\" # bumped by the release script\n"));
+        assert!(toml.contains("titles = { en = \"Min\", es = \"Mínimo\" } # more languages soon\n"));
+        assert!(toml.contains("[vendor] # the studio\n"));
+        assert!(toml.contains("# The only way to play, for now\n[[scenarios]]\nname = \"solo\"\n"));
+        assert!(toml.contains("\n# Beginners\n# A simple game for beginners\n[[scenarios]]\n"));
+
+        let game = editor.manifest();
+        assert_eq!(game.version(), &Version::new(9, 9, 0));
+        assert_eq!(game.scenarios().len(), 2);
+        assert_eq!(game.scenarios()[0].players()[0].io(), &vec![requirement]);
+        assert_eq!(game.scenarios()[1], scenario);
+        assert_eq!(&GameManifest::from_toml(&toml).unwrap(), game);
+    }
+
+    #[test]
+    fn it_adds_and_removes_providers() {
+        let peripheral = Peripheral::rf_card_reader().build();
+        let toml = annotated::peripheral_manifest(&peripheral, "en").unwrap();
+        let mut editor: Editor<PeripheralManifest> = Editor::parse(&toml).unwrap();
+
+        let mut provider = peripheral.provides()[0].clone();
+        provider.set_name("second_reader".try_into().unwrap());
+        editor.add_provider(&provider).unwrap();
+        assert_eq!(editor.manifest().provides().len(), 2);
+
+        editor
+            .remove_provider("peripheral_defined_card_reader")
+            .unwrap();
+        assert_eq!(editor.manifest().provides(), &vec![provider]);
+        assert_eq!(
+            editor
+                .to_string()
+                .matches("\n# Card\n# A card\n[[provides]]")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn it_reports_edits_it_cannot_make() {
+        let mut editor: Editor<GameManifest> = Editor::parse(HAND_WRITTEN).unwrap();
+        let requirement = Game::simple_battle().build().scenarios()[0].players()[0].io()[0].clone();
+
+        assert_eq!(
+            editor.add_requirement("campaign", "hero", &requirement),
+            Err(vec!["no scenario `campaign`".to_string()])
+        );
+        assert_eq!(
+            editor.add_requirement("solo", "villain", &requirement),
+            Err(vec!["no player type `villain`".to_string()])
+        );
+        assert_eq!(editor.to_string(), HAND_WRITTEN);
+
+        assert!(Editor::<GameManifest>::parse("key = \"no-version\"").is_err());
+    }
+
+    #[test]
+    fn it_undoes_edits_that_leave_the_manifest_invalid() {
+        let mut editor: Editor<GameManifest> = Editor::parse(HAND_WRITTEN).unwrap();
+        let solo = editor.manifest().scenarios()[0].clone();
+
+        assert_eq!(
+            editor.add_scenario(&solo),
+            Err(vec![
+                "scenario `solo` is declared more than once".to_string()
+            ])
+        );
+        assert_eq!(editor.to_string(), HAND_WRITTEN);
+        assert_eq!(editor.manifest().scenarios().len(), 1);
+    }
+
+    #[test]
+    fn it_reports_lists_it_cannot_append_to() {
+        let toml = HAND_WRITTEN.replace("io = []", "io = \"none\"");
+        let mut document: toml_edit::DocumentMut = toml.parse().unwrap();
+        let requirement = Game::simple_battle().build().scenarios()[0].players()[0].io()[0].clone();
+        let table = super::new_table(&requirement, "scenarios.players.io").unwrap();
+        let scenario = super::find(document.as_table_mut(), "scenarios", "solo").unwrap();
+        let player = super::find(scenario.table, "players", "hero").unwrap();
+
+        assert_eq!(
+            super::append(player, "io", table),
+            Err("`io` is not a list of tables".to_string())
+        );
+    }
+}
@@ -4,6 +4,10 @@ use std::fmt::Debug;
 
 use common::data::serialization::{Jsonable, Streamable, Tomlable};
 
+use crate::yaml::Yamlable;
+
+pub mod devices;
+pub mod expansions;
 pub mod features;
 pub mod games;
 pub mod peripherals;
@@ -15,10 +19,17 @@ pub trait Example {
     type BuiltValue: Jsonable<Entity = Self::BuiltValue>
         + Tomlable<Entity = Self::BuiltValue>
         + Streamable<Entity = Self::BuiltValue>
+        + Yamlable
         + PartialEq
         + Debug;
 
     fn json(&self) -> &str;
+
+    /// The same example written as YAML, for examples that have a twin under `examples/`.
+    fn yaml(&self) -> Option<&str> {
+        None
+    }
+
     fn build(&self) -> Self::BuiltValue {
         Self::BuiltValue::from_json(self.json()).expect("Failed to build Feature")
     }
@@ -43,4 +54,12 @@ where
     let deserialized: T::BuiltValue = T::BuiltValue::from_json(&serialized).unwrap();
 
     assert_eq!(deserialized, built);
+
+    // The YAML twin says the same thing, and survives its own round trip
+    if let Some(yaml) = example.yaml() {
+        assert_eq!(T::BuiltValue::from_yaml(yaml).unwrap(), built);
+
+        let serialized = built.to_yaml().unwrap();
+        assert_eq!(T::BuiltValue::from_yaml(&serialized).unwrap(), built);
+    }
 }
@@ -0,0 +1,51 @@
+use crate::device::DeviceInstance;
+use crate::examples::Example;
+
+pub struct Device {
+    json: String,
+    yaml: String,
+}
+
+impl Example for Device {
+    type BuiltValue = DeviceInstance;
+
+    fn json(&self) -> &str {
+        &self.json
+    }
+
+    fn yaml(&self) -> Option<&str> {
+        Some(&self.yaml)
+    }
+}
+
+impl Device {
+    pub fn min() -> Self {
+        Self {
+            json: include_str!("../../examples/devices/min.json").to_string(),
+            yaml: include_str!("../../examples/devices/min.yaml").to_string(),
+        }
+    }
+
+    pub fn rf_card_reader() -> Self {
+        Self {
+            json: include_str!("../../examples/devices/rf.card_reader.json").to_string(),
+            yaml: include_str!("../../examples/devices/rf.card_reader.yaml").to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::examples;
+    use crate::examples::devices::Device;
+
+    #[test]
+    fn it_serializes_min() {
+        examples::run_example_round_trip_test(Device::min);
+    }
+
+    #[test]
+    fn it_serializes_rf_card_reader() {
+        examples::run_example_round_trip_test(Device::rf_card_reader);
+    }
+}
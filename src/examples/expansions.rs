@@ -0,0 +1,45 @@
+use crate::examples::Example;
+use crate::expansion::ExpansionManifest;
+
+pub struct Expansion {
+    json: String,
+    yaml: String,
+}
+
+impl Example for Expansion {
+    type BuiltValue = ExpansionManifest;
+
+    fn json(&self) -> &str {
+        &self.json
+    }
+
+    fn yaml(&self) -> Option<&str> {
+        Some(&self.yaml)
+    }
+}
+
+impl Expansion {
+    pub fn simple_battle_dragons() -> Self {
+        Self {
+            json: include_str!(
+                "../../examples/expansions/simple_battle_dragons/simple_battle_dragons.json"
+            )
+            .to_string(),
+            yaml: include_str!(
+                "../../examples/expansions/simple_battle_dragons/simple_battle_dragons.yaml"
+            )
+            .to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::examples;
+    use crate::examples::expansions::Expansion;
+
+    #[test]
+    fn it_serializes_simple_battle_dragons() {
+        examples::run_example_round_trip_test(Expansion::simple_battle_dragons);
+    }
+}
@@ -3,6 +3,7 @@ use crate::game::GameManifest;
 
 pub struct Game {
     json: String,
+    yaml: String,
 }
 
 impl Example for Game {
@@ -11,18 +12,24 @@ impl Example for Game {
     fn json(&self) -> &str {
         &self.json
     }
+
+    fn yaml(&self) -> Option<&str> {
+        Some(&self.yaml)
+    }
 }
 
 impl Game {
     pub fn min() -> Self {
         Self {
             json: include_str!("../../examples/games/min.json").to_string(),
+            yaml: include_str!("../../examples/games/min.yaml").to_string(),
         }
     }
 
     pub fn simple_battle() -> Self {
         Self {
             json: include_str!("../../examples/games/simple_battle/simple_battle.json").to_string(),
+            yaml: include_str!("../../examples/games/simple_battle/simple_battle.yaml").to_string(),
         }
     }
 }
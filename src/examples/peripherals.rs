@@ -3,6 +3,7 @@ use crate::peripheral::PeripheralManifest;
 
 pub struct Peripheral {
     json: String,
+    yaml: String,
 }
 
 impl Example for Peripheral {
@@ -11,12 +12,17 @@ impl Example for Peripheral {
     fn json(&self) -> &str {
         &self.json
     }
+
+    fn yaml(&self) -> Option<&str> {
+        Some(&self.yaml)
+    }
 }
 
 impl Peripheral {
     pub fn min() -> Self {
         Self {
             json: include_str!("../../examples/peripherals/min.json").to_string(),
+            yaml: include_str!("../../examples/peripherals/min.yaml").to_string(),
         }
     }
 
@@ -24,6 +30,8 @@ impl Peripheral {
         Self {
             json: include_str!("../../examples/peripherals/rf.card_reader/rf.card_reader.json")
                 .to_string(),
+            yaml: include_str!("../../examples/peripherals/rf.card_reader/rf.card_reader.yaml")
+                .to_string(),
         }
     }
 
@@ -31,12 +39,15 @@ impl Peripheral {
         Self {
             json: include_str!("../../examples/peripherals/rf.dice_pad/rf.dice_pad.json")
                 .to_string(),
+            yaml: include_str!("../../examples/peripherals/rf.dice_pad/rf.dice_pad.yaml")
+                .to_string(),
         }
     }
 
     pub fn rf_screen() -> Self {
         Self {
             json: include_str!("../../examples/peripherals/rf.screen/rf.screen.json").to_string(),
+            yaml: include_str!("../../examples/peripherals/rf.screen/rf.screen.yaml").to_string(),
         }
     }
 
@@ -46,6 +57,10 @@ impl Peripheral {
                 "../../examples/peripherals/watertribe.card_reader/watertribe.card_reader.json"
             )
             .to_string(),
+            yaml: include_str!(
+                "../../examples/peripherals/watertribe.card_reader/watertribe.card_reader.yaml"
+            )
+            .to_string(),
         }
     }
 }
@@ -2,6 +2,7 @@ use crate::examples::Example;
 
 pub struct Spec {
     json: String,
+    yaml: String,
 }
 
 impl Example for Spec {
@@ -10,30 +11,47 @@ impl Example for Spec {
     fn json(&self) -> &str {
         &self.json
     }
+
+    fn yaml(&self) -> Option<&str> {
+        Some(&self.yaml)
+    }
 }
 
 impl Spec {
     pub fn min() -> Self {
         Self {
             json: include_str!("../../examples/specs/min.json").to_string(),
+            yaml: include_str!("../../examples/specs/min.yaml").to_string(),
         }
     }
 
     pub fn card() -> Self {
         Self {
             json: include_str!("../../examples/specs/card/card.json").to_string(),
+            yaml: include_str!("../../examples/specs/card/card.yaml").to_string(),
         }
     }
 
     pub fn dice() -> Self {
         Self {
             json: include_str!("../../examples/specs/dice/dice.json").to_string(),
+            yaml: include_str!("../../examples/specs/dice/dice.yaml").to_string(),
         }
     }
 
     pub fn screen() -> Self {
         Self {
             json: include_str!("../../examples/specs/screen/screen.json").to_string(),
+            yaml: include_str!("../../examples/specs/screen/screen.yaml").to_string(),
+        }
+    }
+
+    pub fn watertribe_card() -> Self {
+        Self {
+            json: include_str!("../../examples/specs/watertribe_card/watertribe_card.json")
+                .to_string(),
+            yaml: include_str!("../../examples/specs/watertribe_card/watertribe_card.yaml")
+                .to_string(),
         }
     }
 
@@ -66,4 +84,9 @@ mod test {
     fn it_serializes_screen() {
         examples::run_example_round_trip_test(Spec::screen);
     }
+
+    #[test]
+    fn it_serializes_watertribe_card() {
+        examples::run_example_round_trip_test(Spec::watertribe_card);
+    }
 }
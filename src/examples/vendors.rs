@@ -2,6 +2,7 @@ use crate::examples::Example;
 
 pub struct Vendor {
     json: String,
+    yaml: String,
 }
 
 impl Example for Vendor {
@@ -10,18 +11,24 @@ impl Example for Vendor {
     fn json(&self) -> &str {
         &self.json
     }
+
+    fn yaml(&self) -> Option<&str> {
+        Some(&self.yaml)
+    }
 }
 
 impl Vendor {
     pub fn min() -> Self {
         Self {
             json: include_str!("../../examples/vendor/min.json").to_string(),
+            yaml: include_str!("../../examples/vendor/min.yaml").to_string(),
         }
     }
 
     pub fn ravenfire() -> Self {
         Self {
             json: include_str!("../../examples/vendor/ravenfire.json").to_string(),
+            yaml: include_str!("../../examples/vendor/ravenfire.yaml").to_string(),
         }
     }
 
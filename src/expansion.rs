@@ -0,0 +1,375 @@
+//! Expansion packs that build on a base `GameManifest`.
+//!
+//! An expansion references its base game by key and `VersionReq`, adds scenarios, and patches
+//! existing ones. `GameManifest::with_expansions()` produces the effective manifest.
+use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
+
+use common::data::key::ValidKey;
+use common::data::LanguageMap;
+use common::macros::{Jsonable, Streamable, Tomlable};
+use common::semver::{Version, VersionReq};
+
+use crate::game::{GameManifest, Requirement, Scenario, ScenarioPlayer};
+use crate::vendor::Vendor;
+use crate::Manifest;
+
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct ExpansionManifest {
+    key: ValidKey,
+    #[serde(default)]
+    #[builder(default)]
+    titles: LanguageMap,
+    #[serde(default)]
+    #[builder(default)]
+    descriptions: LanguageMap,
+    version: Version,
+    vendor: Vendor,
+    /// The game this expands
+    base: BaseGame,
+    /// Scenarios that are new in this expansion
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    scenarios: Vec<Scenario>,
+    /// Changes to scenarios in the base game or in other expansions
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    patches: Vec<ScenarioPatch>,
+}
+
+/// Which base game, and which versions of it, an expansion works with.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct BaseGame {
+    key: ValidKey,
+    version: VersionReq,
+}
+
+/// Changes an expansion makes to an existing scenario.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct ScenarioPatch {
+    scenario: ValidKey,
+    #[serde(default)]
+    #[builder(setter(into, strip_option), default)]
+    titles: Option<LanguageMap>,
+    #[serde(default)]
+    #[builder(setter(into, strip_option), default)]
+    descriptions: Option<LanguageMap>,
+    /// Player types to add. A player type with the same name replaces the existing one.
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    players: Vec<ScenarioPlayer>,
+    /// Requirements added to existing player types
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    requirements: Vec<PlayerRequirements>,
+}
+
+/// Requirements to add to a single player type.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct PlayerRequirements {
+    player: ValidKey,
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    io: Vec<Requirement>,
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    playables: Vec<Requirement>,
+}
+
+impl ScenarioPatch {
+    fn apply(&self, scenario: &mut Scenario) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+
+        if let Some(titles) = &self.titles {
+            scenario.set_titles(titles.clone());
+        }
+        if let Some(descriptions) = &self.descriptions {
+            scenario.set_descriptions(descriptions.clone());
+        }
+
+        let mut players = scenario.players().clone();
+        for player in &self.players {
+            match players
+                .iter_mut()
+                .find(|existing| existing.name() == player.name())
+            {
+                Some(existing) => *existing = player.clone(),
+                None => players.push(player.clone()),
+            }
+        }
+
+        for addition in &self.requirements {
+            match players
+                .iter_mut()
+                .find(|existing| existing.name() == &addition.player)
+            {
+                Some(player) => {
+                    let mut io = player.io().clone();
+                    io.extend(addition.io.iter().cloned());
+                    player.set_io(io);
+
+                    let mut playables = player.playables().clone();
+                    playables.extend(addition.playables.iter().cloned());
+                    player.set_playables(playables);
+                }
+                None => errors.push(format!(
+                    "scenario `{}` has no player type `{}`",
+                    self.scenario, addition.player
+                )),
+            }
+        }
+        scenario.set_players(players);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl GameManifest {
+    /// Builds the effective manifest for this game with the given expansions installed.
+    ///
+    /// Fails if an expansion targets another game or version, if two expansions add the same
+    /// scenario, if a scenario is patched more than once, or if the effective manifest does not
+    /// validate.
+    pub fn with_expansions(
+        &self,
+        expansions: &[ExpansionManifest],
+    ) -> Result<GameManifest, Vec<String>> {
+        let mut errors = vec![];
+        let mut game = self.clone();
+        let mut scenarios = game.scenarios().clone();
+
+        for expansion in expansions {
+            if expansion.base.key != *self.key() {
+                errors.push(format!(
+                    "expansion `{}` is for `{}`, not `{}`",
+                    expansion.key,
+                    expansion.base.key,
+                    self.key()
+                ));
+            } else if !expansion.base.version.matches(self.version()) {
+                errors.push(format!(
+                    "expansion `{}` requires `{}` version `{}`, found `{}`",
+                    expansion.key,
+                    self.key(),
+                    expansion.base.version,
+                    self.version()
+                ));
+            }
+
+            for scenario in &expansion.scenarios {
+                if scenarios.iter().any(|s| s.name() == scenario.name()) {
+                    errors.push(format!(
+                        "expansion `{}` adds scenario `{}`, which already exists",
+                        expansion.key,
+                        scenario.name()
+                    ));
+                } else {
+                    scenarios.push(scenario.clone());
+                }
+            }
+        }
+
+        // Patches apply once every scenario has been added so expansions can patch each other
+        let mut patched: Vec<(String, String)> = vec![];
+        for expansion in expansions {
+            for patch in &expansion.patches {
+                let name = patch.scenario.to_string();
+
+                if let Some((_, other)) = patched.iter().find(|(scenario, _)| *scenario == name) {
+                    errors.push(if *other == expansion.key.to_string() {
+                        format!(
+                            "expansion `{}` patches scenario `{}` more than once",
+                            other, name
+                        )
+                    } else {
+                        format!(
+                            "expansions `{}` and `{}` both patch scenario `{}`",
+                            other, expansion.key, name
+                        )
+                    });
+                    continue;
+                }
+                patched.push((name.clone(), expansion.key.to_string()));
+
+                match scenarios.iter_mut().find(|s| s.name().to_string() == name) {
+                    Some(scenario) => {
+                        if let Err(patch_errors) = patch.apply(scenario) {
+                            errors.extend(
+                                patch_errors.into_iter().map(|error| {
+                                    format!("expansion `{}`: {}", expansion.key, error)
+                                }),
+                            );
+                        }
+                    }
+                    None => errors.push(format!(
+                        "expansion `{}` patches unknown scenario `{}`",
+                        expansion.key, name
+                    )),
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        game.set_scenarios(scenarios);
+        game.validate()?;
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::examples::expansions::Expansion;
+    use crate::examples::games::Game;
+    use crate::examples::Example;
+
+    #[test]
+    fn it_composes_a_base_game_with_an_expansion() {
+        let game = Game::simple_battle()
+            .build()
+            .with_expansions(&[Expansion::simple_battle_dragons().build()])
+            .expect("Failed to compose expansion");
+
+        assert_eq!(game.scenarios().len(), 3);
+
+        let advanced = game.scenario("advanced").unwrap();
+        let community = advanced
+            .players()
+            .iter()
+            .find(|p| p.name().to_string() == "community")
+            .unwrap();
+        assert_eq!(community.io().len(), 3);
+    }
+
+    #[test]
+    fn it_detects_conflicting_patches() {
+        let mut other = Expansion::simple_battle_dragons().build();
+        other.set_key("simple-battle-goblins".try_into().unwrap());
+        other.set_scenarios(vec![]);
+
+        let errors = Game::simple_battle()
+            .build()
+            .with_expansions(&[Expansion::simple_battle_dragons().build(), other])
+            .unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                "expansions `simple-battle-dragons` and `simple-battle-goblins` both patch scenario `advanced`"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn it_detects_patching_a_scenario_twice() {
+        let mut expansion = Expansion::simple_battle_dragons().build();
+        let mut patches = expansion.patches().clone();
+        patches.push(patches[0].clone());
+        expansion.set_patches(patches);
+
+        let errors = Game::simple_battle()
+            .build()
+            .with_expansions(&[expansion])
+            .unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                "expansion `simple-battle-dragons` patches scenario `advanced` more than once"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn it_validates_the_effective_manifest() {
+        let mut expansion = Expansion::simple_battle_dragons().build();
+        let mut scenarios = expansion.scenarios().clone();
+        let mut players = scenarios[0].players().clone();
+        players.push(players[0].clone());
+        scenarios[0].set_players(players);
+        expansion.set_scenarios(scenarios);
+
+        let errors = Game::simple_battle()
+            .build()
+            .with_expansions(&[expansion])
+            .unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                "scenario `dragon_hunt`: player type `champion` is declared more than once"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn it_rejects_an_expansion_for_another_version() {
+        let mut game = Game::simple_battle().build();
+        game.set_version("10.0.0".parse().unwrap());
+
+        assert!(game
+            .with_expansions(&[Expansion::simple_battle_dragons().build()])
+            .is_err());
+    }
+}
@@ -1,14 +1,25 @@
 use getset::{Getters, MutGetters, Setters};
 
+use common::chrono::NaiveDate;
 use common::data::key::ValidKey;
+use common::data::schema::Property;
 use common::data::LanguageMap;
 use common::macros::{Jsonable, Streamable, Tomlable};
 use common::semver::{Version, VersionReq};
 use common::serde::{Deserialize, Serialize};
 use common::url::Url;
 
+use crate::composition::{CountConstraint, PlayerComposition, Team};
+use crate::deprecation::Deprecation;
+use crate::introspect::PropertyShape;
+use crate::payload::{self, Payload, Violation};
+use crate::predicate::Predicate;
 use crate::range::Range;
+use crate::registry::SpecRegistry;
+use crate::runtime::RuntimeDependent;
+use crate::specs::SpecIdentifier;
 use crate::vendor::Vendor;
+use crate::Manifest;
 
 #[derive(
     Tomlable,
@@ -38,6 +49,25 @@ pub struct GameManifest {
     support: Option<Url>,
     vendor: Vendor,
     scenarios: Vec<Scenario>,
+    /// Settings the host can configure, such as difficulty or round count
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    options: Vec<Property>,
+    #[serde(default)]
+    #[builder(default)]
+    option_defaults: Payload,
+    /// Versions of the Raven Fire runtime this game runs on
+    #[serde(default)]
+    #[builder(default)]
+    requires_runtime: VersionReq,
+    /// Optional runtime capabilities this game cannot run without
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    requires_capabilities: Vec<ValidKey>,
+    /// Set on games that are being retired
+    #[serde(default)]
+    #[builder(default)]
+    deprecated: Option<Deprecation>,
 }
 
 #[derive(
@@ -64,6 +94,20 @@ pub struct Scenario {
     #[builder(default)]
     descriptions: LanguageMap,
     players: Vec<ScenarioPlayer>,
+    /// Settings for this scenario only. These replace game options with the same key.
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    options: Vec<Property>,
+    #[serde(default)]
+    #[builder(default)]
+    option_defaults: Payload,
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    teams: Vec<Team>,
+    /// Rules between player type counts, such as "champions must equal 2× goblins"
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    constraints: Vec<CountConstraint>,
 }
 
 #[derive(
@@ -115,9 +159,474 @@ pub struct ScenarioPlayer {
 pub struct Requirement {
     // Game Defined Group
     name: ValidKey,
-    spec: Url,
+    /// The spec's URL or key
+    spec: SpecIdentifier,
     version: VersionReq,
     count: u8,
     #[serde(default = "Vec::default")]
     features: Vec<ValidKey>,
+    /// Conditions on the provider's static values, such as "width >= 800"
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    predicates: Vec<Predicate>,
+}
+
+impl GameManifest {
+    pub fn scenario(&self, name: &str) -> Option<&Scenario> {
+        self.scenarios.iter().find(|s| s.name.to_string() == name)
+    }
+
+    /// The options available in a scenario, or for the whole game when `scenario` is `None`.
+    pub fn options_for(&self, scenario: Option<&Scenario>) -> Vec<Property> {
+        let mut options = self.options.clone();
+
+        if let Some(scenario) = scenario {
+            for option in &scenario.options {
+                let key = PropertyShape::of(option).key;
+                options.retain(|existing| PropertyShape::of(existing).key != key);
+                options.push(option.clone());
+            }
+        }
+
+        options
+    }
+
+    /// Fills in defaults for a chosen settings document and checks it against the options.
+    ///
+    /// Scenario defaults win over game defaults, and the chosen settings win over both.
+    pub fn resolve_settings(
+        &self,
+        scenario: Option<&Scenario>,
+        chosen: &Payload,
+    ) -> Result<Payload, Vec<Violation>> {
+        let mut settings = self.option_defaults.clone();
+        if let Some(scenario) = scenario {
+            settings.merge(&scenario.option_defaults);
+        }
+        settings.merge(chosen);
+
+        payload::validate(&self.options_for(scenario), &settings)?;
+        Ok(settings)
+    }
+
+    /// Deprecated specs, features, and properties the requirements rely on, looked up in the
+    /// registry, with deprecation messages in `locale` and removal dates as of `today`. These do
+    /// not fail validation.
+    pub fn warnings(
+        &self,
+        registry: &SpecRegistry,
+        locale: &str,
+        today: Option<NaiveDate>,
+    ) -> Vec<String> {
+        let mut warnings = vec![];
+
+        if let Some(deprecation) = &self.deprecated {
+            warnings.push(deprecation.warning(
+                &format!("`{}` {}", self.key, self.version),
+                locale,
+                today,
+            ));
+        }
+        for scenario in &self.scenarios {
+            for player in &scenario.players {
+                for requirement in player.io.iter().chain(&player.playables) {
+                    warnings.extend(
+                        requirement
+                            .deprecation_warnings(registry, locale, today)
+                            .into_iter()
+                            .map(|warning| {
+                                format!(
+                                    "scenario `{}`: {} `{}`: {}",
+                                    scenario.name, player.name, requirement.name, warning
+                                )
+                            }),
+                    );
+                }
+            }
+        }
+
+        warnings
+    }
+}
+
+impl Requirement {
+    /// Warnings for the deprecated spec, features, and predicate properties this requirement
+    /// asks for. Specs the registry does not know are skipped.
+    pub fn deprecation_warnings(
+        &self,
+        registry: &SpecRegistry,
+        locale: &str,
+        today: Option<NaiveDate>,
+    ) -> Vec<String> {
+        let spec = match registry.latest(&self.spec, &self.version) {
+            Some(spec) => spec,
+            None => return vec![],
+        };
+        let properties: Vec<ValidKey> = self
+            .predicates
+            .iter()
+            .map(|predicate| predicate.property().clone())
+            .collect();
+
+        spec.deprecation_warnings(&self.features, &properties, locale, today)
+    }
+}
+
+impl RuntimeDependent for GameManifest {
+    fn runtime_key(&self) -> String {
+        self.key.to_string()
+    }
+
+    fn runtime_requirement(&self) -> &VersionReq {
+        &self.requires_runtime
+    }
+
+    fn capability_requirements(&self) -> &[ValidKey] {
+        &self.requires_capabilities
+    }
+}
+
+impl Manifest for GameManifest {
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+
+        if let Err(violations) = payload::validate_partial(&self.options, &self.option_defaults) {
+            errors.extend(
+                violations
+                    .into_iter()
+                    .map(|violation| format!("option defaults: {}", violation)),
+            );
+        }
+
+        for (index, scenario) in self.scenarios.iter().enumerate() {
+            if self.scenarios[..index]
+                .iter()
+                .any(|earlier| earlier.name == scenario.name)
+            {
+                errors.push(format!(
+                    "scenario `{}` is declared more than once",
+                    scenario.name
+                ));
+            }
+        }
+
+        for scenario in &self.scenarios {
+            let options = self.options_for(Some(scenario));
+            if let Err(violations) = payload::validate_partial(&options, &scenario.option_defaults)
+            {
+                errors.extend(violations.into_iter().map(|violation| {
+                    format!(
+                        "scenario `{}`: option defaults: {}",
+                        scenario.name, violation
+                    )
+                }));
+            }
+
+            if let Err(scenario_errors) = scenario.validate() {
+                errors.extend(
+                    scenario_errors
+                        .into_iter()
+                        .map(|error| format!("scenario `{}`: {}", scenario.name, error)),
+                );
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Scenario {
+    /// Checks that teams and constraints only reference player types this scenario declares.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+        let names: Vec<String> = self.players.iter().map(|p| p.name.to_string()).collect();
+
+        for (index, name) in names.iter().enumerate() {
+            if names[..index].contains(name) {
+                errors.push(format!("player type `{}` is declared more than once", name));
+            }
+        }
+
+        for team in &self.teams {
+            for player in team.players() {
+                if !names.contains(&player.to_string()) {
+                    errors.push(format!(
+                        "team `{}` references unknown player type `{}`",
+                        team.name(),
+                        player
+                    ));
+                }
+            }
+        }
+
+        for constraint in &self.constraints {
+            for term in constraint.terms() {
+                if !names.contains(&term.player().to_string()) {
+                    errors.push(format!(
+                        "constraint `{}` references unknown player type `{}`",
+                        constraint,
+                        term.player()
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Checks a composition against player counts, teams, and constraints.
+    pub fn validate_composition(&self, composition: &PlayerComposition) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+
+        for (name, _) in composition.counts() {
+            if !self.players.iter().any(|p| &p.name.to_string() == name) {
+                errors.push(format!("unknown player type `{}`", name));
+            }
+        }
+
+        for player in &self.players {
+            let count = composition.count_of(&player.name.to_string());
+            if !player.count.contains(count) {
+                errors.push(format!(
+                    "{} `{}` players is outside of `{}`",
+                    count, player.name, player.count
+                ));
+            }
+        }
+
+        for team in &self.teams {
+            let members: u16 = self
+                .players
+                .iter()
+                .filter(|p| team.includes(&p.name.to_string()))
+                .map(|p| u16::from(composition.count_of(&p.name.to_string())))
+                .sum();
+
+            if !team.accepts(members) {
+                errors.push(format!(
+                    "{} players cannot form `{}` teams of `{}` players each",
+                    members,
+                    team.count(),
+                    team.size()
+                ));
+            }
+        }
+
+        for constraint in &self.constraints {
+            if !constraint.holds(composition) {
+                errors.push(format!("constraint `{}` does not hold", constraint));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Every legal player composition for this scenario.
+    ///
+    /// Player types with no upper bound ("2+") are capped at `limit` players.
+    pub fn compositions(&self, limit: u8) -> Vec<PlayerComposition> {
+        let mut candidates = vec![PlayerComposition::new()];
+
+        for player in &self.players {
+            let max = player.count.upper_bound().unwrap_or(limit).min(limit);
+            let name = player.name.to_string();
+
+            candidates = candidates
+                .into_iter()
+                .flat_map(|partial| {
+                    (player.count.min..=max)
+                        .filter(|count| player.count.contains(*count))
+                        .map(|count| partial.clone().with(&name, count))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+        }
+
+        candidates
+            .into_iter()
+            .filter(|composition| self.validate_composition(composition).is_ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::data::serialization::Jsonable;
+
+    use crate::composition::PlayerComposition;
+    use crate::examples::specs::Spec;
+    use crate::examples::Example;
+    use crate::game::{GameManifest, Requirement, Scenario};
+    use crate::payload::Payload;
+    use crate::registry::SpecRegistry;
+    use crate::Manifest;
+
+    fn scenario() -> Scenario {
+        Scenario::from_json(
+            r#"
+            {
+                "name": "skirmish",
+                "players": [
+                    { "name": "game_master", "count": "1-2" },
+                    { "name": "adventurer", "count": "2+" }
+                ],
+                "teams": [
+                    { "name": "party", "players": ["adventurer"], "size": "2", "count": "1-4" }
+                ],
+                "constraints": [
+                    {
+                        "terms": [
+                            { "player": "adventurer" },
+                            { "player": "game_master", "coefficient": -2 }
+                        ],
+                        "relation": "AtLeast"
+                    },
+                    {
+                        "terms": [
+                            { "player": "adventurer" },
+                            { "player": "game_master", "coefficient": -5 }
+                        ],
+                        "relation": "AtMost"
+                    }
+                ]
+            }
+            "#,
+        )
+        .expect("Failed to build scenario")
+    }
+
+    #[test]
+    fn it_validates_a_legal_composition() {
+        let composition = PlayerComposition::new()
+            .with("game_master", 1)
+            .with("adventurer", 4);
+
+        assert_eq!(scenario().validate_composition(&composition), Ok(()));
+    }
+
+    #[test]
+    fn it_rejects_compositions_that_break_constraints() {
+        let composition = PlayerComposition::new()
+            .with("game_master", 2)
+            .with("adventurer", 2);
+
+        assert!(scenario().validate_composition(&composition).is_err());
+    }
+
+    #[test]
+    fn it_enumerates_every_legal_composition() {
+        let compositions = scenario().compositions(12);
+
+        let expected = vec![
+            PlayerComposition::new()
+                .with("game_master", 1)
+                .with("adventurer", 2),
+            PlayerComposition::new()
+                .with("game_master", 1)
+                .with("adventurer", 4),
+            PlayerComposition::new()
+                .with("game_master", 2)
+                .with("adventurer", 4),
+            PlayerComposition::new()
+                .with("game_master", 2)
+                .with("adventurer", 6),
+            PlayerComposition::new()
+                .with("game_master", 2)
+                .with("adventurer", 8),
+        ];
+
+        assert_eq!(compositions, expected);
+    }
+
+    #[test]
+    fn it_resolves_settings_with_defaults() {
+        let game = GameManifest::from_json(
+            r#"
+            {
+                "key": "options-game",
+                "version": "1.0.0",
+                "vendor": { "name": "ravenfire" },
+                "options": [
+                    {
+                        "key": "difficulty",
+                        "data_type": "String",
+                        "enumerations": [{"String": "easy"}, {"String": "hard"}]
+                    },
+                    { "key": "rounds", "data_type": "Integer" }
+                ],
+                "option_defaults": { "difficulty": "easy", "rounds": 3 },
+                "scenarios": [
+                    {
+                        "name": "marathon",
+                        "players": [],
+                        "option_defaults": { "rounds": 10 }
+                    }
+                ]
+            }
+            "#,
+        )
+        .expect("Failed to build game");
+
+        let settings = game
+            .resolve_settings(
+                game.scenario("marathon"),
+                &Payload::new().with("difficulty", "hard"),
+            )
+            .expect("Settings should be valid");
+
+        assert_eq!(
+            settings,
+            Payload::new().with("difficulty", "hard").with("rounds", 10)
+        );
+        assert!(game
+            .resolve_settings(None, &Payload::new().with("difficulty", "nightmare"))
+            .is_err());
+        assert_eq!(game.validate(), Ok(()));
+    }
+
+    #[test]
+    fn it_reports_unknown_player_types() {
+        let mut scenario = scenario();
+        scenario.set_players(vec![]);
+
+        assert_eq!(scenario.validate().map_err(|errors| errors.len()), Err(5));
+    }
+
+    #[test]
+    fn it_warns_about_requirements_on_deprecated_features() {
+        let mut registry = SpecRegistry::new();
+        registry.add(Spec::card().build());
+        let requirement = Requirement::from_json(
+            r#"
+            {
+                "name": "hand",
+                "spec": "ravenfire.card",
+                "version": "^1.2",
+                "count": 1,
+                "features": ["nfc"]
+            }
+            "#,
+        )
+        .expect("Failed to build requirement");
+
+        let warnings = requirement.deprecation_warnings(&registry, "en", None);
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("the `nfc` feature is deprecated since 1.2.0"));
+        assert!(requirement
+            .deprecation_warnings(&SpecRegistry::new(), "en", None)
+            .is_empty());
+    }
 }
@@ -0,0 +1,167 @@
+//! Read-only views over schema types owned by `common`.
+//!
+//! `Property` lives in `common`, so we read it through its serialized form. This keeps
+//! everything that needs to know a property's type, optionality, or enumerations in one place.
+use serde_json::Value;
+
+use common::data::schema::Property;
+use common::data::LanguageMap;
+
+/// The data types a `Property` can declare.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DataKind {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    Playable,
+    Coordinate2,
+    Coordinate3,
+    Other(String),
+}
+
+impl DataKind {
+    fn parse(name: &str) -> Self {
+        match name {
+            "String" => DataKind::String,
+            "Integer" => DataKind::Integer,
+            "Float" => DataKind::Float,
+            "Boolean" => DataKind::Boolean,
+            "Playable" => DataKind::Playable,
+            "Coordinate2" => DataKind::Coordinate2,
+            "Coordinate3" => DataKind::Coordinate3,
+            other => DataKind::Other(other.to_string()),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            DataKind::String => "String",
+            DataKind::Integer => "Integer",
+            DataKind::Float => "Float",
+            DataKind::Boolean => "Boolean",
+            DataKind::Playable => "Playable",
+            DataKind::Coordinate2 => "Coordinate2",
+            DataKind::Coordinate3 => "Coordinate3",
+            DataKind::Other(name) => name,
+        }
+    }
+
+    /// Whether a single (non-collection) value has this data type.
+    ///
+    /// Playables are referenced by key and coordinates are either `{x, y[, z]}` or `[x, y[, z]]`.
+    pub(crate) fn accepts(&self, value: &Value) -> bool {
+        match self {
+            DataKind::String | DataKind::Playable => value.is_string(),
+            DataKind::Integer => value.is_i64() || value.is_u64(),
+            DataKind::Float => value.is_number(),
+            DataKind::Boolean => value.is_boolean(),
+            DataKind::Coordinate2 => is_coordinate(value, &["x", "y"]),
+            DataKind::Coordinate3 => is_coordinate(value, &["x", "y", "z"]),
+            DataKind::Other(_) => true,
+        }
+    }
+}
+
+fn is_coordinate(value: &Value, axes: &[&str]) -> bool {
+    match value {
+        Value::Array(items) => items.len() == axes.len() && items.iter().all(Value::is_number),
+        Value::Object(map) => {
+            map.len() == axes.len()
+                && axes
+                    .iter()
+                    .all(|axis| map.get(*axis).map(Value::is_number).unwrap_or(false))
+        }
+        _ => false,
+    }
+}
+
+/// A `Property` as we need to read it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PropertyShape {
+    pub key: String,
+    pub data_type: DataKind,
+    pub optional: bool,
+    pub collection: bool,
+    pub titles: Vec<(String, String)>,
+    pub descriptions: Vec<(String, String)>,
+    /// Allowed values, already unwrapped from their `{"String": "up"}` form
+    pub enumerations: Vec<Value>,
+}
+
+impl PropertyShape {
+    pub(crate) fn of(property: &Property) -> Self {
+        let value = serde_json::to_value(property).unwrap_or(Value::Null);
+
+        Self {
+            key: value["key"].as_str().unwrap_or_default().to_string(),
+            data_type: DataKind::parse(data_type_name(&value["data_type"])),
+            optional: value["optional"].as_bool().unwrap_or(false),
+            collection: value["collection"].as_bool().unwrap_or(false),
+            titles: language_entries(&value["titles"]),
+            descriptions: language_entries(&value["descriptions"]),
+            enumerations: value["enumerations"]
+                .as_array()
+                .map(|items| items.iter().map(unwrap_tagged).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Whether a value is allowed by the enumerations, if there are any.
+    pub(crate) fn is_enumerated(&self, value: &Value) -> bool {
+        self.enumerations.is_empty() || self.enumerations.iter().any(|allowed| allowed == value)
+    }
+}
+
+fn data_type_name(value: &Value) -> &str {
+    match value {
+        Value::String(name) => name,
+        // Data types that carry data serialize as `{"Name": ...}`
+        Value::Object(map) => map.keys().next().map(String::as_str).unwrap_or_default(),
+        _ => "",
+    }
+}
+
+/// `{"String": "up"}` becomes `"up"`
+fn unwrap_tagged(value: &Value) -> Value {
+    match value {
+        Value::Object(map) if map.len() == 1 => map.values().next().cloned().unwrap_or_default(),
+        other => other.clone(),
+    }
+}
+
+/// The `(locale, text)` pairs of a serialized `LanguageMap`, sorted by locale.
+pub(crate) fn language_entries(value: &Value) -> Vec<(String, String)> {
+    let mut entries: Vec<(String, String)> = value
+        .as_object()
+        .map(|map| {
+            map.iter()
+                .filter_map(|(locale, text)| Some((locale.clone(), text.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+/// Text from a `LanguageMap` in the given locale, falling back to English and then to any locale.
+pub(crate) fn localized(map: &LanguageMap, locale: &str) -> Option<String> {
+    pick(
+        &language_entries(&serde_json::to_value(map).unwrap_or(Value::Null)),
+        locale,
+    )
+}
+
+/// Like `localized`, for entries that were already read with `language_entries`.
+pub(crate) fn pick(entries: &[(String, String)], locale: &str) -> Option<String> {
+    let find = |wanted: &str| {
+        entries
+            .iter()
+            .find(|(locale, _)| locale == wanted)
+            .map(|(_, text)| text.clone())
+    };
+
+    find(locale)
+        .or_else(|| find("en"))
+        .or_else(|| entries.first().map(|(_, text)| text.clone()))
+}
@@ -0,0 +1,318 @@
+//! Where things physically are on a peripheral, so apps can show players which slot is theirs.
+//!
+//! A `PeripheralManifest` may carry a `Layout` for its body and each `Provider` may place its
+//! slots with `SlotPosition`s. Coordinates are in millimeters from the top left corner of the
+//! device as it sits in landscape. `render_svg()` draws the result.
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
+
+use common::data::LanguageMap;
+use common::macros::{Jsonable, Streamable, Tomlable};
+
+use crate::introspect;
+use crate::matcher::Assignment;
+use crate::peripheral::PeripheralManifest;
+use crate::{PlayerIndex, PlayerType};
+
+/// How the device sits in front of players.
+#[derive(
+    Tomlable, Jsonable, Streamable, Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    #[default]
+    Landscape,
+    /// Turned a quarter turn clockwise from the landscape coordinates
+    Portrait,
+}
+
+/// The outline of a peripheral.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Layout {
+    width: f32,
+    height: f32,
+    #[serde(default)]
+    #[builder(default)]
+    orientation: Orientation,
+}
+
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Point {
+    x: f32,
+    y: f32,
+}
+
+/// Where one slot of a provider is on the device.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct SlotPosition {
+    /// Zero based slot index
+    slot: u8,
+    /// Top left corner
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    /// Degrees clockwise around the slot's center
+    #[serde(default)]
+    #[builder(default)]
+    rotation: f32,
+    /// Printed on or next to the slot
+    /// { en: "Slot 1", es: "Ranura 1" }
+    #[serde(default)]
+    #[builder(default)]
+    labels: LanguageMap,
+    /// Center of the slot's indicator light, if it has one
+    #[serde(default)]
+    #[builder(default)]
+    indicator: Option<Point>,
+}
+
+impl SlotPosition {
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Whether the slot (ignoring rotation) and its indicator are inside the layout.
+    pub fn fits(&self, layout: &Layout) -> bool {
+        let inside =
+            |x: f32, y: f32| x >= 0.0 && y >= 0.0 && x <= layout.width && y <= layout.height;
+
+        inside(self.x, self.y)
+            && inside(self.x + self.width, self.y + self.height)
+            && self
+                .indicator
+                .as_ref()
+                .map(|point| inside(point.x, point.y))
+                .unwrap_or(true)
+    }
+}
+
+/// Colors (any SVG paint, like "#e63946" or "teal") for each player.
+pub type PlayerColors = HashMap<(PlayerType, PlayerIndex), String>;
+
+/// Draws a peripheral with the slots assigned to players filled in with their colors.
+///
+/// `peripheral` is the manifest's index in the list given to the `Matcher`, so only the
+/// assignments for this peripheral are drawn. Labels use `locale`, falling back to English.
+/// Peripherals without a layout are drawn as the bounding box of their slots.
+pub fn render_svg(
+    manifest: &PeripheralManifest,
+    peripheral: usize,
+    assignments: &[Assignment],
+    colors: &PlayerColors,
+    locale: &str,
+) -> String {
+    let layout = manifest
+        .layout()
+        .clone()
+        .unwrap_or_else(|| bounds(manifest));
+    let (width, height) = match layout.orientation {
+        Orientation::Landscape => (layout.width, layout.height),
+        Orientation::Portrait => (layout.height, layout.width),
+    };
+    let transform = match layout.orientation {
+        Orientation::Landscape => String::new(),
+        Orientation::Portrait => format!(r#" transform="translate({} 0) rotate(90)""#, width),
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(svg, r#"<g{}>"#, transform);
+    let _ = writeln!(
+        svg,
+        r#"<rect class="peripheral" width="{}" height="{}" fill="white" stroke="black"/>"#,
+        layout.width, layout.height
+    );
+
+    for provider in manifest.provides() {
+        for position in provider.positions() {
+            let fill = assignments
+                .iter()
+                .find(|assignment| {
+                    assignment.peripheral == peripheral
+                        && assignment.provider.to_string() == provider.name().to_string()
+                        && assignment.slots.contains(&position.slot)
+                })
+                .and_then(|assignment| colors.get(&(assignment.player.clone(), assignment.index)))
+                .map(|color| escape(color))
+                .unwrap_or_else(|| "none".to_string());
+            let (cx, cy) = position.center();
+
+            let _ = writeln!(
+                svg,
+                r#"<rect class="slot" data-provider="{}" data-slot="{}" x="{}" y="{}" width="{}" height="{}" transform="rotate({} {} {})" fill="{}" stroke="black"/>"#,
+                escape(&provider.name().to_string()),
+                position.slot,
+                position.x,
+                position.y,
+                position.width,
+                position.height,
+                position.rotation,
+                cx,
+                cy,
+                fill
+            );
+            if let Some(indicator) = &position.indicator {
+                let _ = writeln!(
+                    svg,
+                    r#"<circle class="indicator" cx="{}" cy="{}" r="2" fill="{}" stroke="black"/>"#,
+                    indicator.x, indicator.y, fill
+                );
+            }
+            if let Some(label) = introspect::localized(&position.labels, locale) {
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" text-anchor="middle" dominant-baseline="middle">{}</text>"#,
+                    cx,
+                    cy,
+                    escape(&label)
+                );
+            }
+        }
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// The smallest landscape layout that holds every positioned slot.
+fn bounds(manifest: &PeripheralManifest) -> Layout {
+    let (width, height) = manifest
+        .provides()
+        .iter()
+        .flat_map(|provider| provider.positions())
+        .fold((0.0f32, 0.0f32), |(width, height), position| {
+            (
+                width.max(position.x + position.width),
+                height.max(position.y + position.height),
+            )
+        });
+
+    Layout {
+        width,
+        height,
+        orientation: Orientation::Landscape,
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::composition::PlayerComposition;
+    use crate::examples::games::Game;
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::Example;
+    use crate::layout::{render_svg, Orientation, PlayerColors};
+    use crate::matcher::Matcher;
+    use crate::Manifest;
+
+    #[test]
+    fn it_draws_assigned_slots_in_player_colors() {
+        let game = Game::simple_battle().build();
+        let scenario = game.scenario("beginners").unwrap();
+        let peripherals = vec![
+            Peripheral::watertribe_card_reader().build(),
+            Peripheral::rf_card_reader().build(),
+            Peripheral::rf_dice_pad().build(),
+        ];
+        let assignments = Matcher::new(&peripherals)
+            .assign(
+                scenario,
+                &PlayerComposition::new()
+                    .with("champion", 2)
+                    .with("community", 1),
+            )
+            .expect("Failed to assign slots");
+        let colors: PlayerColors = [(("community".to_string(), 0), "teal".to_string())]
+            .into_iter()
+            .collect();
+
+        let svg = render_svg(&peripherals[1], 1, &assignments, &colors, "es");
+
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="70""#)
+        );
+        assert!(svg.contains(r#"data-slot="0" x="10" y="10" width="30" height="40" transform="rotate(0 25 30)" fill="teal""#));
+        assert!(svg.contains(r#"data-slot="1" x="48" y="10" width="30" height="40" transform="rotate(0 63 30)" fill="none""#));
+        assert!(svg.contains(r#"<circle class="indicator" cx="25" cy="58" r="2" fill="teal""#));
+        assert!(svg.contains(">Ranura 1</text>"));
+        assert_eq!(svg.matches("<text").count(), 5);
+    }
+
+    #[test]
+    fn it_turns_portrait_peripherals() {
+        let mut reader = Peripheral::rf_card_reader().build();
+        let mut layout = reader.layout().clone().unwrap();
+        layout.set_orientation(Orientation::Portrait);
+        reader.set_layout(Some(layout));
+
+        let svg = render_svg(&reader, 0, &[], &PlayerColors::new(), "en");
+
+        assert!(svg.contains(r#"width="70" height="200""#));
+        assert!(svg.contains(r#"<g transform="translate(70 0) rotate(90)">"#));
+        assert_eq!(reader.validate(), Ok(()));
+    }
+
+    #[test]
+    fn it_draws_peripherals_without_a_layout() {
+        let mut reader = Peripheral::rf_card_reader().build();
+        reader.set_layout(None);
+
+        let svg = render_svg(&reader, 0, &[], &PlayerColors::new(), "en");
+
+        assert!(svg.contains(r#"<rect class="peripheral" width="192" height="50""#));
+    }
+}
@@ -14,13 +14,33 @@ use common::data::serialization::{Streamable as StreamableTrait, Tomlable as Tom
 
 // use serde::{Deserialize, Serialize};
 
+pub mod annotated;
+pub mod codegen;
+pub mod composition;
+pub mod connection;
+pub mod deprecation;
+pub mod device;
+pub mod diff;
+pub mod editor;
 pub mod examples;
+pub mod expansion;
 pub mod game;
+mod introspect;
+pub mod layout;
+pub mod matcher;
 mod meta;
+pub mod payload;
 pub mod peripheral;
+pub mod pkl;
+pub mod predicate;
 pub mod range;
+pub mod registry;
+pub mod runtime;
+pub mod schema;
+pub mod simulator;
 mod specs;
 mod vendor;
+pub mod yaml;
 
 // TODO: [implementation] Is this the best place for these
 /// The game defined name of a player's io
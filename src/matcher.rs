@@ -0,0 +1,715 @@
+//! Matching a game's requirements to the peripherals on the table.
+//!
+//! `check()` answers whether a single `Provider` can satisfy a single `Requirement`.
+//! `Matcher` hands out provider slots to every player in a scenario.
+use common::chrono::NaiveDate;
+use common::data::key::ValidKey;
+
+use crate::composition::PlayerComposition;
+use crate::game::{Requirement, Scenario};
+use crate::peripheral::{PeripheralManifest, Provider};
+use crate::registry::SpecRegistry;
+use crate::specs::{SpecIdentifier, SpecReference};
+use crate::{GameDefinedGroup, PeripheralDefinedGroup, PlayerIndex, PlayerType};
+
+/// Explains every reason a provider cannot satisfy a requirement.
+///
+/// A provider whose spec extends the required spec satisfies it as well.
+pub fn check(requirement: &Requirement, provider: &Provider) -> Result<(), Vec<String>> {
+    check_lineage(
+        requirement,
+        requirement.spec(),
+        provider,
+        &provider.spec().lineage(),
+    )
+}
+
+/// Like `check`, with the required spec and the specs the provider's spec can stand in for
+/// already looked up.
+fn check_lineage(
+    requirement: &Requirement,
+    wanted: &SpecIdentifier,
+    provider: &Provider,
+    lineage: &[SpecReference],
+) -> Result<(), Vec<String>> {
+    let mut reasons = vec![];
+    let spec = provider.spec();
+    match lineage.iter().find(|reference| wanted.matches(reference)) {
+        None => reasons.push(format!(
+            "provides `{}`, not `{}`",
+            spec.url(),
+            requirement.spec()
+        )),
+        Some(reference) if !requirement.version().matches(reference.version()) => {
+            reasons.push(format!(
+                "provides version `{}`, which does not match `{}`",
+                reference.version(),
+                requirement.version()
+            ))
+        }
+        Some(_) => {}
+    }
+
+    let features = spec.implemented_feature_keys();
+    let missing: Vec<&ValidKey> = requirement
+        .features()
+        .iter()
+        .filter(|feature| !features.contains(feature))
+        .collect();
+    for feature in &missing {
+        reasons.push(format!("does not implement the `{}` feature", feature));
+    }
+
+    let capable = provider.slots_with(requirement.features()).len();
+    if provider.count() < requirement.count() {
+        reasons.push(format!(
+            "has {} slots, but {} are required",
+            provider.count(),
+            requirement.count()
+        ));
+    } else if missing.is_empty() && capable < *requirement.count() as usize {
+        reasons.push(format!(
+            "has {} slots with the required features, but {} are required",
+            capable,
+            requirement.count()
+        ));
+    }
+
+    for predicate in requirement.predicates() {
+        if !predicate.holds(provider.values()) {
+            reasons.push(format!("does not satisfy `{}`", predicate));
+        }
+    }
+
+    if reasons.is_empty() {
+        Ok(())
+    } else {
+        Err(reasons)
+    }
+}
+
+/// Slots of one provider given to one player for one requirement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub player: PlayerType,
+    pub index: PlayerIndex,
+    pub group: GameDefinedGroup,
+    /// Index of the peripheral in the list given to the `Matcher`
+    pub peripheral: usize,
+    pub provider: PeripheralDefinedGroup,
+    pub slots: Vec<u8>,
+}
+
+/// Assigns provider slots on a set of peripherals to the players of a scenario.
+pub struct Matcher<'a> {
+    peripherals: &'a [PeripheralManifest],
+    registry: Option<&'a SpecRegistry>,
+    /// Peripherals, specs, and features removed by this date are not used
+    today: Option<NaiveDate>,
+}
+
+impl<'a> Matcher<'a> {
+    pub fn new(peripherals: &'a [PeripheralManifest]) -> Self {
+        Self {
+            peripherals,
+            registry: None,
+            today: None,
+        }
+    }
+
+    /// Stops using peripherals, specs, and features whose removal date has come by `today`.
+    /// Without a date, removal dates are only reported as warnings.
+    pub fn on(mut self, today: NaiveDate) -> Self {
+        self.today = Some(today);
+        self
+    }
+
+    /// Follows `extends` chains and URL aliases through the registry, so providers of a
+    /// grandchild spec satisfy requirements on the grandparent, and requirements written
+    /// against an old URL still match.
+    pub fn with_registry(mut self, registry: &'a SpecRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Assigns slots for every requirement of every player in the composition.
+    ///
+    /// Players are served in scenario order and each requirement takes the first provider that
+    /// satisfies it and still has enough free slots with the required features. Slots without
+    /// extra features are used first, so featured slots stay free for requirements that need
+    /// them. Deprecated providers are passed over while anything else fits, and providers
+    /// whose removal date has come are not used at all. Requirements that cannot be met are
+    /// reported, along with why each candidate provider was rejected.
+    pub fn assign(
+        &self,
+        scenario: &Scenario,
+        composition: &PlayerComposition,
+    ) -> Result<Vec<Assignment>, Vec<String>> {
+        let mut used: Vec<Vec<Vec<bool>>> = self
+            .peripherals
+            .iter()
+            .map(|peripheral| {
+                peripheral
+                    .provides()
+                    .iter()
+                    .map(|provider| vec![false; *provider.count() as usize])
+                    .collect()
+            })
+            .collect();
+        let mut assignments = vec![];
+        let mut errors = vec![];
+
+        for player in scenario.players() {
+            let name = player.name().to_string();
+
+            for index in 0..composition.count_of(&name) {
+                // Requirements that need more features pick their slots first
+                let mut requirements: Vec<&Requirement> =
+                    player.io().iter().chain(player.playables()).collect();
+                requirements
+                    .sort_by_key(|requirement| std::cmp::Reverse(requirement.features().len()));
+
+                for requirement in requirements {
+                    match self.assign_requirement(requirement, &mut used) {
+                        Ok((peripheral, provider, slots)) => assignments.push(Assignment {
+                            player: name.clone(),
+                            index,
+                            group: requirement.name().clone(),
+                            peripheral,
+                            provider,
+                            slots,
+                        }),
+                        Err(reasons) => errors.push(format!(
+                            "{} {} `{}`: {}",
+                            name,
+                            index,
+                            requirement.name(),
+                            reasons.join("; ")
+                        )),
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(assignments)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn assign_requirement(
+        &self,
+        requirement: &Requirement,
+        used: &mut [Vec<Vec<bool>>],
+    ) -> Result<(usize, ValidKey, Vec<u8>), Vec<String>> {
+        let mut reasons = vec![];
+
+        // Deprecated peripherals, specs, and features only get used when nothing else fits
+        let mut providers: Vec<(usize, usize)> = self
+            .peripherals
+            .iter()
+            .enumerate()
+            .flat_map(|(peripheral_index, peripheral)| {
+                (0..peripheral.provides().len())
+                    .map(move |provider_index| (peripheral_index, provider_index))
+            })
+            .collect();
+        providers.sort_by_key(|(peripheral_index, provider_index)| {
+            let peripheral = &self.peripherals[*peripheral_index];
+            peripheral.is_deprecated_with(
+                &peripheral.provides()[*provider_index],
+                requirement.features(),
+            )
+        });
+
+        for (peripheral_index, provider_index) in providers {
+            let peripheral = &self.peripherals[peripheral_index];
+            let provider = &peripheral.provides()[provider_index];
+            let lineage = match self.registry {
+                Some(registry) => match registry.lineage(provider.spec()) {
+                    Ok(lineage) => lineage,
+                    Err(lineage_errors) => {
+                        reasons.push(format!(
+                            "`{}` `{}` {}",
+                            peripheral.key(),
+                            provider.name(),
+                            lineage_errors.join(", ")
+                        ));
+                        continue;
+                    }
+                },
+                None => provider.spec().lineage(),
+            };
+            let wanted = self
+                .registry
+                .map(|registry| registry.identify(requirement.spec()))
+                .unwrap_or_else(|| requirement.spec().clone());
+
+            if let Err(provider_reasons) = check_lineage(requirement, &wanted, provider, &lineage) {
+                reasons.push(format!(
+                    "`{}` `{}` {}",
+                    peripheral.key(),
+                    provider.name(),
+                    provider_reasons.join(", ")
+                ));
+                continue;
+            }
+
+            let removals = self
+                .today
+                .map(|today| peripheral.removals(provider, requirement.features(), today))
+                .unwrap_or_default();
+            if !removals.is_empty() {
+                reasons.push(format!(
+                    "`{}` `{}`: {}",
+                    peripheral.key(),
+                    provider.name(),
+                    removals.join(", ")
+                ));
+                continue;
+            }
+
+            // Slots with the fewest extra features go first, keeping featured slots free
+            // for the requirements that need them
+            let slots = &mut used[peripheral_index][provider_index];
+            let mut candidates: Vec<u8> = provider
+                .slots_with(requirement.features())
+                .into_iter()
+                .filter(|slot| !slots[*slot as usize])
+                .collect();
+            candidates.sort_by_key(|slot| (provider.features_for(*slot).len(), *slot));
+
+            let mut free: Vec<u8> = candidates
+                .into_iter()
+                .take(*requirement.count() as usize)
+                .collect();
+            free.sort();
+
+            if free.len() < *requirement.count() as usize {
+                reasons.push(format!(
+                    "`{}` `{}` has {} free slots",
+                    peripheral.key(),
+                    provider.name(),
+                    free.len()
+                ));
+                continue;
+            }
+
+            for slot in &free {
+                slots[*slot as usize] = true;
+            }
+            return Ok((peripheral_index, provider.name().clone(), free));
+        }
+
+        if reasons.is_empty() {
+            reasons.push("no peripherals".to_string());
+        }
+        Err(reasons)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::chrono::NaiveDate;
+    use common::data::serialization::Jsonable;
+    use common::url::Url;
+
+    use crate::composition::PlayerComposition;
+    use crate::examples::games::Game;
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::Example;
+    use crate::game::{Requirement, Scenario};
+    use crate::matcher::{check, Matcher};
+    use crate::peripheral::{PeripheralManifest, Provider};
+    use crate::registry::SpecRegistry;
+    use crate::specs::SpecIdentifier;
+
+    fn screen() -> Provider {
+        Provider::from_json(
+            r#"
+            {
+                "name": "main_screen",
+                "count": 1,
+                "values": { "width": 1920, "height": 1080 },
+                "spec": {
+                    "key": "ravenfire.screen",
+                    "url": "https://ravenfire.games/dev/specs/screen.json",
+                    "version": "10.1.2",
+                    "vendor": { "name": "ravenfire" },
+                    "properties": [
+                        { "key": "width", "data_type": "Integer" },
+                        { "key": "height", "data_type": "Integer" }
+                    ]
+                }
+            }
+            "#,
+        )
+        .expect("Failed to build provider")
+    }
+
+    fn screen_requirement(min_width: u32) -> Requirement {
+        Requirement::from_json(&format!(
+            r#"
+            {{
+                "name": "board",
+                "spec": "https://ravenfire.games/dev/specs/screen.json",
+                "version": ">=10",
+                "count": 1,
+                "predicates": [
+                    {{ "property": "width", "comparison": "AtLeast", "value": {} }}
+                ]
+            }}
+            "#,
+            min_width
+        ))
+        .expect("Failed to build requirement")
+    }
+
+    #[test]
+    fn it_enforces_predicates_on_static_values() {
+        assert_eq!(check(&screen_requirement(800), &screen()), Ok(()));
+        assert_eq!(
+            check(&screen_requirement(2560), &screen()),
+            Err(vec!["does not satisfy `width >= 2560`".to_string()])
+        );
+        assert_eq!(screen().validate(), Ok(()));
+    }
+
+    #[test]
+    fn it_rejects_static_values_outside_the_spec() {
+        let mut provider = screen();
+        provider.set_values(provider.values().clone().with("width", "wide"));
+
+        assert!(provider.validate().is_err());
+    }
+
+    #[test]
+    fn it_assigns_slots_to_every_player() {
+        let game = Game::simple_battle().build();
+        let scenario = game.scenario("beginners").unwrap();
+        let peripherals = vec![
+            Peripheral::watertribe_card_reader().build(),
+            Peripheral::rf_card_reader().build(),
+            Peripheral::rf_dice_pad().build(),
+        ];
+        let composition = PlayerComposition::new()
+            .with("champion", 2)
+            .with("community", 1);
+
+        let assignments = Matcher::new(&peripherals)
+            .assign(scenario, &composition)
+            .expect("Failed to assign slots");
+
+        let summary: Vec<(String, u8, usize, Vec<u8>)> = assignments
+            .into_iter()
+            .map(|a| (a.player, a.index, a.peripheral, a.slots))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("champion".to_string(), 0, 0, vec![0, 1, 2, 3, 4]),
+                ("champion".to_string(), 0, 0, vec![0]),
+                ("champion".to_string(), 1, 0, vec![5, 6, 7, 8, 9]),
+                ("champion".to_string(), 1, 2, vec![0]),
+                ("community".to_string(), 0, 1, vec![0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_saves_featured_slots_for_requirements_that_need_them() {
+        let reader = PeripheralManifest::from_json(
+            r#"
+            {
+                "key": "rf.led_reader",
+                "version": "1.0.0",
+                "vendor": { "name": "ravenfire" },
+                "provides": [
+                    {
+                        "name": "reader",
+                        "count": 5,
+                        "slots": [{ "slots": [0, 1], "features": [] }],
+                        "spec": {
+                            "key": "ravenfire.card",
+                            "url": "https://ravenfire.games/dev/specs/card.json",
+                            "version": "1.2.3",
+                            "vendor": { "name": "ravenfire" },
+                            "features": [{ "key": "led" }]
+                        }
+                    }
+                ]
+            }
+            "#,
+        )
+        .expect("Failed to build peripheral");
+        let scenario = Scenario::from_json(
+            r#"
+            {
+                "name": "lights",
+                "players": [
+                    {
+                        "name": "champion",
+                        "count": 1,
+                        "io": [
+                            {
+                                "name": "hand",
+                                "spec": "https://ravenfire.games/dev/specs/card.json",
+                                "version": "^1.2",
+                                "count": 2
+                            },
+                            {
+                                "name": "spells",
+                                "spec": "https://ravenfire.games/dev/specs/card.json",
+                                "version": "^1.2",
+                                "count": 3,
+                                "features": ["led"]
+                            }
+                        ]
+                    }
+                ]
+            }
+            "#,
+        )
+        .expect("Failed to build scenario");
+        let peripherals = vec![reader];
+
+        let assignments = Matcher::new(&peripherals)
+            .assign(&scenario, &PlayerComposition::new().with("champion", 1))
+            .expect("Failed to assign slots");
+
+        let slots: Vec<(String, Vec<u8>)> = assignments
+            .into_iter()
+            .map(|a| (a.group.to_string(), a.slots))
+            .collect();
+        assert_eq!(
+            slots,
+            vec![
+                ("spells".to_string(), vec![2, 3, 4]),
+                ("hand".to_string(), vec![0, 1]),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_explains_unmet_requirements() {
+        let game = Game::simple_battle().build();
+        let scenario = game.scenario("beginners").unwrap();
+        let peripherals = vec![Peripheral::rf_card_reader().build()];
+        let composition = PlayerComposition::new()
+            .with("champion", 2)
+            .with("community", 1);
+
+        let errors = Matcher::new(&peripherals)
+            .assign(scenario, &composition)
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 4);
+    }
+
+    #[test]
+    fn it_accepts_providers_of_extending_specs() {
+        let mut provider = screen();
+        provider.set_spec(crate::examples::specs::Spec::watertribe_card().build());
+        provider.set_values(Default::default());
+        let requirement = Requirement::from_json(
+            r#"
+            {
+                "name": "hand",
+                "spec": "https://ravenfire.games/dev/specs/card.json",
+                "version": "^1.2",
+                "count": 1,
+                "features": ["facing", "foil"]
+            }
+            "#,
+        )
+        .expect("Failed to build requirement");
+
+        assert_eq!(check(&requirement, &provider), Ok(()));
+
+        let mut nfc = requirement.clone();
+        nfc.set_features(vec!["nfc".try_into().unwrap()]);
+        assert_eq!(
+            check(&nfc, &provider),
+            Err(vec!["does not implement the `nfc` feature".to_string()])
+        );
+    }
+
+    #[test]
+    fn it_matches_requirements_by_key_and_by_aliased_url() {
+        let peripherals = vec![Peripheral::rf_card_reader().build()];
+        let scenario = |spec: &str| {
+            Scenario::from_json(&format!(
+                r#"
+                {{
+                    "name": "moved",
+                    "players": [
+                        {{
+                            "name": "champion",
+                            "count": 1,
+                            "io": [
+                                {{ "name": "hand", "spec": "{}", "version": "^1.2", "count": 1 }}
+                            ]
+                        }}
+                    ]
+                }}
+                "#,
+                spec
+            ))
+            .expect("Failed to build scenario")
+        };
+        let composition = PlayerComposition::new().with("champion", 1);
+        let old_url = "https://ravenfire.games/specs/card.json";
+        let mut registry = SpecRegistry::new();
+        registry.add_alias(
+            Url::parse(old_url).unwrap(),
+            "ravenfire.card".try_into().unwrap(),
+        );
+
+        let by_key = scenario("ravenfire.card");
+        assert!(matches!(
+            by_key.players()[0].io()[0].spec(),
+            SpecIdentifier::Key(_)
+        ));
+        assert!(Matcher::new(&peripherals)
+            .assign(&by_key, &composition)
+            .is_ok());
+
+        let moved = scenario(old_url);
+        assert!(Matcher::new(&peripherals)
+            .assign(&moved, &composition)
+            .is_err());
+        assert!(Matcher::new(&peripherals)
+            .with_registry(&registry)
+            .assign(&moved, &composition)
+            .is_ok());
+    }
+
+    #[test]
+    fn it_reports_specs_the_registry_cannot_follow() {
+        let mut peripheral = Peripheral::rf_card_reader().build();
+        let mut providers = peripheral.provides().clone();
+        providers[0].set_spec(crate::examples::specs::Spec::watertribe_card().build());
+        peripheral.set_provides(providers);
+        let peripherals = vec![peripheral];
+        let scenario = Scenario::from_json(
+            r#"
+            {
+                "name": "solo",
+                "players": [
+                    {
+                        "name": "champion",
+                        "count": 1,
+                        "io": [
+                            { "name": "hand", "spec": "ravenfire.card", "version": "^1.2", "count": 1 }
+                        ]
+                    }
+                ]
+            }
+            "#,
+        )
+        .expect("Failed to build scenario");
+        let composition = PlayerComposition::new().with("champion", 1);
+
+        assert!(Matcher::new(&peripherals)
+            .assign(&scenario, &composition)
+            .is_ok());
+
+        let errors = Matcher::new(&peripherals)
+            .with_registry(&SpecRegistry::new())
+            .assign(&scenario, &composition)
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec!["champion 0 `hand`: `rf.card_reader` `peripheral_defined_card_reader` `https://watertribe.example/specs/card.json` extends `https://ravenfire.games/dev/specs/card.json` 1.2.3, which is not registered".to_string()]
+        );
+    }
+
+    #[test]
+    fn it_prefers_providers_that_are_not_deprecated() {
+        let mut retired = Peripheral::rf_card_reader().build();
+        retired.set_deprecated(Some(
+            serde_json::from_str(r#"{ "since": "2.18.0", "replacement": "rf.card_reader_v2" }"#)
+                .unwrap(),
+        ));
+        let peripherals = vec![retired, Peripheral::rf_card_reader().build()];
+        let scenario = Scenario::from_json(
+            r#"
+            {
+                "name": "solo",
+                "players": [
+                    {
+                        "name": "champion",
+                        "count": 1,
+                        "io": [
+                            {
+                                "name": "hand",
+                                "spec": "ravenfire.card",
+                                "version": "^1.2",
+                                "count": 5
+                            }
+                        ]
+                    }
+                ]
+            }
+            "#,
+        )
+        .expect("Failed to build scenario");
+        let composition = PlayerComposition::new().with("champion", 1);
+
+        let assignments = Matcher::new(&peripherals)
+            .assign(&scenario, &composition)
+            .expect("Failed to assign slots");
+        assert_eq!(assignments[0].peripheral, 1);
+
+        // Deprecated providers still fill in once nothing else fits
+        let composition = PlayerComposition::new().with("champion", 2);
+        let assignments = Matcher::new(&peripherals)
+            .assign(&scenario, &composition)
+            .expect("Failed to assign slots");
+        let used: Vec<usize> = assignments.iter().map(|a| a.peripheral).collect();
+        assert_eq!(used, vec![1, 0]);
+    }
+
+    #[test]
+    fn it_stops_using_providers_once_they_are_removed() {
+        let mut retired = Peripheral::rf_card_reader().build();
+        retired.set_deprecated(Some(
+            serde_json::from_str(r#"{ "since": "2.18.0", "removal": "2025-06-30" }"#).unwrap(),
+        ));
+        let peripherals = vec![retired];
+        let scenario = Scenario::from_json(
+            r#"
+            {
+                "name": "solo",
+                "players": [
+                    {
+                        "name": "champion",
+                        "count": 1,
+                        "io": [
+                            { "name": "hand", "spec": "ravenfire.card", "version": "^1.2", "count": 5 }
+                        ]
+                    }
+                ]
+            }
+            "#,
+        )
+        .expect("Failed to build scenario");
+        let composition = PlayerComposition::new().with("champion", 1);
+        let matcher =
+            |day| Matcher::new(&peripherals).on(NaiveDate::from_ymd_opt(2025, 6, day).unwrap());
+
+        // Removal dates are only enforced for a given day
+        assert!(Matcher::new(&peripherals)
+            .assign(&scenario, &composition)
+            .is_ok());
+        assert!(matcher(29).assign(&scenario, &composition).is_ok());
+        assert_eq!(
+            matcher(30).assign(&scenario, &composition),
+            Err(vec![
+                "champion 0 `hand`: `rf.card_reader` `peripheral_defined_card_reader`: `rf.card_reader` 2.18.1 was removed on 2025-06-30"
+                    .to_string()
+            ])
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Property values and validation against a list of `Property` definitions.
+//!
+//! The same check is used for game settings and anything else that is described by properties.
+use std::fmt::{Display, Formatter};
+
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use common::data::schema::Property;
+use common::macros::{Jsonable, Streamable, Tomlable};
+
+use crate::introspect::PropertyShape;
+
+/// A flat set of property values keyed by property key.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Clone,
+    PartialEq,
+    Default,
+)]
+#[serde(transparent)]
+#[getset(get = "pub")]
+pub struct Payload {
+    values: Map<String, Value>,
+}
+
+impl Payload {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a value, replacing any previous value for the key.
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.values.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    /// Copies every value from `other` over this payload.
+    pub fn merge(&mut self, other: &Payload) {
+        for (key, value) in &other.values {
+            self.values.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// A single way a payload fails to match its properties.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// The payload could not be read at all
+    Malformed { reason: String },
+    /// An enabled feature the spec does not declare
+    UnknownFeature { feature: String },
+    /// A command the spec and enabled features do not declare
+    UnknownCommand { command: String },
+    /// An event the spec and enabled features do not declare
+    UnknownEvent { event: String },
+    /// The payload has a key no property declares
+    UnknownKey { key: String },
+    /// A non-optional property has no value
+    Missing { key: String },
+    /// The value is not of the property's `data_type`
+    TypeMismatch {
+        key: String,
+        expected: String,
+        found: Value,
+    },
+    /// The value is not one of the property's `enumerations`
+    NotEnumerated {
+        key: String,
+        value: Value,
+        allowed: Vec<Value>,
+    },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::Malformed { reason } => write!(f, "the payload is malformed: {}", reason),
+            Violation::UnknownFeature { feature } => {
+                write!(f, "`{}` is not a feature of the spec", feature)
+            }
+            Violation::UnknownCommand { command } => {
+                write!(f, "`{}` is not a command of the spec", command)
+            }
+            Violation::UnknownEvent { event } => {
+                write!(f, "`{}` is not an event of the spec", event)
+            }
+            Violation::UnknownKey { key } => write!(f, "`{}` is not a known property", key),
+            Violation::Missing { key } => write!(f, "`{}` is required", key),
+            Violation::TypeMismatch {
+                key,
+                expected,
+                found,
+            } => write!(f, "`{}` should be {} but found `{}`", key, expected, found),
+            Violation::NotEnumerated {
+                key,
+                value,
+                allowed,
+            } => {
+                let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                write!(
+                    f,
+                    "`{}` is not one of [{}] for `{}`",
+                    value,
+                    allowed.join(", "),
+                    key
+                )
+            }
+        }
+    }
+}
+
+/// Checks a payload against a list of properties.
+///
+/// Reports unknown keys, missing non-optional properties, type mismatches, and values
+/// outside of `enumerations`.
+pub fn validate(properties: &[Property], payload: &Payload) -> Result<(), Vec<Violation>> {
+    check(properties, payload, true)
+}
+
+/// Like `validate`, but a payload that leaves properties out is fine.
+///
+/// Used for defaults and static values that only describe some of the properties.
+pub fn validate_partial(properties: &[Property], payload: &Payload) -> Result<(), Vec<Violation>> {
+    check(properties, payload, false)
+}
+
+fn check(
+    properties: &[Property],
+    payload: &Payload,
+    require_all: bool,
+) -> Result<(), Vec<Violation>> {
+    let shapes: Vec<PropertyShape> = properties.iter().map(PropertyShape::of).collect();
+    let mut violations = vec![];
+
+    for key in payload.values.keys() {
+        if !shapes.iter().any(|shape| &shape.key == key) {
+            violations.push(Violation::UnknownKey { key: key.clone() });
+        }
+    }
+
+    for shape in &shapes {
+        match payload.values.get(&shape.key) {
+            None | Some(Value::Null) => {
+                if require_all && !shape.optional {
+                    violations.push(Violation::Missing {
+                        key: shape.key.clone(),
+                    });
+                }
+            }
+            Some(value) => check_value(shape, value, &mut violations),
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+fn check_value(shape: &PropertyShape, value: &Value, violations: &mut Vec<Violation>) {
+    let items: Vec<&Value> = if shape.collection {
+        match value {
+            Value::Array(items) => items.iter().collect(),
+            other => {
+                violations.push(Violation::TypeMismatch {
+                    key: shape.key.clone(),
+                    expected: format!("a collection of {}", shape.data_type.name()),
+                    found: other.clone(),
+                });
+                return;
+            }
+        }
+    } else {
+        vec![value]
+    };
+
+    for item in items {
+        if !shape.data_type.accepts(item) {
+            violations.push(Violation::TypeMismatch {
+                key: shape.key.clone(),
+                expected: shape.data_type.name().to_string(),
+                found: item.clone(),
+            });
+        } else if !shape.is_enumerated(item) {
+            violations.push(Violation::NotEnumerated {
+                key: shape.key.clone(),
+                value: item.clone(),
+                allowed: shape.enumerations.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::examples::properties::Property;
+    use crate::examples::Example;
+    use crate::payload::{validate, validate_partial, Payload, Violation};
+
+    #[test]
+    fn it_accepts_a_valid_payload() {
+        let properties = vec![Property::facing().build(), Property::weapon().build()];
+        let payload = Payload::new().with("facing", "up");
+
+        assert_eq!(validate(&properties, &payload), Ok(()));
+    }
+
+    #[test]
+    fn it_reports_every_violation() {
+        let properties = vec![Property::facing().build(), Property::min().build()];
+        let payload = Payload::new()
+            .with("facing", "sideways")
+            .with("color", "red");
+
+        let violations = validate(&properties, &payload).unwrap_err();
+
+        assert_eq!(
+            violations,
+            vec![
+                Violation::UnknownKey {
+                    key: "color".to_string()
+                },
+                Violation::NotEnumerated {
+                    key: "facing".to_string(),
+                    value: json!("sideways"),
+                    allowed: vec![json!("up"), json!("down")],
+                },
+                Violation::Missing {
+                    key: "strength".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_type_mismatches() {
+        let properties = vec![Property::min().build()];
+        let payload = Payload::new().with("strength", "strong");
+
+        assert!(matches!(
+            validate(&properties, &payload).unwrap_err()[..],
+            [Violation::TypeMismatch { .. }]
+        ));
+    }
+
+    #[test]
+    fn it_allows_missing_values_when_partial() {
+        let properties = vec![Property::facing().build(), Property::min().build()];
+
+        assert_eq!(validate_partial(&properties, &Payload::new()), Ok(()));
+    }
+}
@@ -1,17 +1,25 @@
 use getset::{Getters, Setters};
 use serde::{Deserialize, Serialize};
 
+use common::chrono::NaiveDate;
 use common::data::key::ValidKey;
 use common::data::LanguageMap;
 use common::url::Url;
 use common::{
     macros::{Jsonable, Streamable, Tomlable},
-    semver::Version,
+    semver::{Version, VersionReq},
 };
 
+use crate::connection::Connection;
+use crate::deprecation::Deprecation;
+use crate::layout::{Layout, SlotPosition};
+use crate::payload::{self, Payload};
+use crate::runtime::RuntimeDependent;
 use crate::specs::Spec;
 use crate::vendor::Vendor;
+use crate::Manifest;
 
+/// Describes a peripheral model. Individual devices are `DeviceInstance`s.
 #[derive(
     Tomlable,
     Jsonable,
@@ -34,9 +42,6 @@ pub struct PeripheralManifest {
     /// "1.23.01-alpha"
     version: Version,
 
-    // Serial number of this device
-    uuid: ValidKey,
-
     /// Vendor of the device
     vendor: Vendor,
 
@@ -63,6 +68,137 @@ pub struct PeripheralManifest {
     /// A list of specs it implements
     #[serde(default)]
     provides: Vec<Provider>,
+
+    /// The ways a hub can reach the device
+    #[serde(default)]
+    #[builder(default)]
+    connections: Vec<Connection>,
+
+    /// The outline of the device, for drawing it
+    #[serde(default)]
+    #[builder(default)]
+    layout: Option<Layout>,
+
+    /// Versions of the Raven Fire runtime (and protocol) the firmware speaks
+    #[serde(default)]
+    #[builder(default)]
+    requires_runtime: VersionReq,
+
+    /// Optional runtime capabilities the device cannot work without
+    #[serde(default)]
+    #[builder(default)]
+    requires_capabilities: Vec<ValidKey>,
+
+    /// Set on models or firmware that are being retired
+    #[serde(default)]
+    #[builder(default)]
+    deprecated: Option<Deprecation>,
+}
+
+impl PeripheralManifest {
+    /// Deprecated things this peripheral is or uses, such as an old firmware or a feature its
+    /// spec is retiring, with deprecation messages in `locale` and removal dates as of `today`.
+    /// These do not fail validation.
+    pub fn warnings(&self, locale: &str, today: Option<NaiveDate>) -> Vec<String> {
+        let mut warnings = vec![];
+
+        if let Some(deprecation) = &self.deprecated {
+            warnings.push(deprecation.warning(
+                &format!("`{}` {}", self.key, self.version),
+                locale,
+                today,
+            ));
+        }
+        for provider in &self.provides {
+            warnings.extend(
+                provider
+                    .deprecation_warnings(locale, today)
+                    .into_iter()
+                    .map(|warning| format!("provider `{}`: {}", provider.name, warning)),
+            );
+        }
+
+        warnings
+    }
+
+    /// What is gone by `today` among the peripheral itself and what a provider implements for
+    /// the given features.
+    pub fn removals(
+        &self,
+        provider: &Provider,
+        features: &[ValidKey],
+        today: NaiveDate,
+    ) -> Vec<String> {
+        let mut removals = vec![];
+
+        if let Some(removal) = self.deprecated.as_ref().and_then(|d| d.removed_on(today)) {
+            removals.push(format!(
+                "`{}` {} was removed on {}",
+                self.key, self.version, removal
+            ));
+        }
+        removals.extend(provider.spec.removals(features, today));
+
+        removals
+    }
+
+    /// Whether the peripheral, or what a provider implements for the given features, is
+    /// deprecated.
+    pub fn is_deprecated_with(&self, provider: &Provider, features: &[ValidKey]) -> bool {
+        self.deprecated.is_some() || provider.spec.is_deprecated_with(features)
+    }
+}
+
+impl Manifest for PeripheralManifest {
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+
+        for provider in &self.provides {
+            if let Err(provider_errors) = provider.validate() {
+                errors.extend(provider_errors);
+            }
+        }
+
+        for connection in &self.connections {
+            if let Err(connection_errors) = connection.validate() {
+                errors.extend(connection_errors);
+            }
+        }
+
+        if let Some(layout) = &self.layout {
+            for provider in &self.provides {
+                for position in provider.positions() {
+                    if !position.fits(layout) {
+                        errors.push(format!(
+                            "provider `{}`: slot {} is outside of the layout",
+                            provider.name(),
+                            position.slot()
+                        ));
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl RuntimeDependent for PeripheralManifest {
+    fn runtime_key(&self) -> String {
+        self.key.to_string()
+    }
+
+    fn runtime_requirement(&self) -> &VersionReq {
+        &self.requires_runtime
+    }
+
+    fn capability_requirements(&self) -> &[ValidKey] {
+        &self.requires_capabilities
+    }
 }
 
 #[derive(
@@ -84,4 +220,220 @@ pub struct Provider {
     name: ValidKey,
     spec: Spec,
     count: u8,
+    /// Fixed capabilities, such as a screen's resolution, keyed by spec property
+    #[serde(default)]
+    #[builder(default)]
+    values: Payload,
+    /// Slots that only implement some of the spec's features.
+    /// Slots not listed here implement every feature in `spec`.
+    #[serde(default)]
+    #[builder(default)]
+    slots: Vec<SlotGroup>,
+    /// Where each slot physically is
+    #[serde(default)]
+    #[builder(default)]
+    positions: Vec<SlotPosition>,
+}
+
+/// A set of slots with their own feature list, such as the 3 of 5 slots that have LEDs.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct SlotGroup {
+    /// Zero based slot indexes
+    slots: Vec<u8>,
+    #[serde(default)]
+    #[builder(default)]
+    features: Vec<ValidKey>,
+}
+
+impl Provider {
+    /// The features a single slot implements.
+    pub fn features_for(&self, slot: u8) -> Vec<ValidKey> {
+        match self.slots.iter().find(|group| group.slots.contains(&slot)) {
+            Some(group) => group.features.clone(),
+            None => self.spec.implemented_feature_keys(),
+        }
+    }
+
+    /// The slots that implement every one of the given features.
+    pub fn slots_with(&self, features: &[ValidKey]) -> Vec<u8> {
+        (0..self.count)
+            .filter(|slot| {
+                let implemented = self.features_for(*slot);
+                features.iter().all(|feature| implemented.contains(feature))
+            })
+            .collect()
+    }
+
+    /// Warnings for the deprecated spec, features, and valued properties this provider uses.
+    pub fn deprecation_warnings(&self, locale: &str, today: Option<NaiveDate>) -> Vec<String> {
+        let mut features: Vec<ValidKey> = vec![];
+        for slot in 0..self.count {
+            for feature in self.features_for(slot) {
+                if !features.contains(&feature) {
+                    features.push(feature);
+                }
+            }
+        }
+        let properties: Vec<ValidKey> = self
+            .values
+            .values()
+            .keys()
+            .filter_map(|key| ValidKey::try_from(key.as_str()).ok())
+            .collect();
+
+        self.spec
+            .deprecation_warnings(&features, &properties, locale, today)
+    }
+
+    /// Checks the static values against the spec's (and implemented features') properties,
+    /// and that slot groups and positions only use real slots and features.
+    ///
+    /// Values for properties a spec inherits are only known once the spec is resolved.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+        let properties = self.spec.state_with(&self.spec.feature_keys());
+
+        if let Err(violations) = payload::validate_partial(&properties, &self.values) {
+            errors.extend(
+                violations
+                    .into_iter()
+                    .map(|violation| violation.to_string()),
+            );
+        }
+
+        let mut seen: Vec<u8> = vec![];
+        for group in &self.slots {
+            for slot in &group.slots {
+                if *slot >= self.count {
+                    errors.push(format!("slot {} is outside of {} slots", slot, self.count));
+                } else if seen.contains(slot) {
+                    errors.push(format!("slot {} is in more than one slot group", slot));
+                }
+                seen.push(*slot);
+            }
+
+            let implemented = self.spec.implemented_feature_keys();
+            for feature in &group.features {
+                if !implemented.contains(feature) {
+                    errors.push(format!("slot feature `{}` is not in the spec", feature));
+                }
+            }
+        }
+
+        let mut positioned: Vec<u8> = vec![];
+        for position in &self.positions {
+            let slot = *position.slot();
+            if slot >= self.count {
+                errors.push(format!(
+                    "position of slot {} is outside of {} slots",
+                    slot, self.count
+                ));
+            } else if positioned.contains(&slot) {
+                errors.push(format!("slot {} has more than one position", slot));
+            }
+            positioned.push(slot);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors
+                .into_iter()
+                .map(|error| format!("provider `{}`: {}", self.name, error))
+                .collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::data::serialization::Jsonable;
+
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::Example;
+    use crate::peripheral::Provider;
+
+    fn reader() -> Provider {
+        Provider::from_json(
+            r#"
+            {
+                "name": "reader",
+                "count": 5,
+                "slots": [
+                    { "slots": [3, 4], "features": ["rfid"] }
+                ],
+                "spec": {
+                    "key": "ravenfire.card",
+                    "url": "https://ravenfire.games/dev/specs/card.json",
+                    "version": "1.2.3",
+                    "vendor": { "name": "ravenfire" },
+                    "features": [
+                        { "key": "rfid" },
+                        { "key": "led" }
+                    ]
+                }
+            }
+            "#,
+        )
+        .expect("Failed to build provider")
+    }
+
+    #[test]
+    fn it_describes_features_per_slot() {
+        let reader = reader();
+        let led = vec!["led".try_into().unwrap()];
+
+        assert_eq!(reader.features_for(0).len(), 2);
+        assert_eq!(reader.features_for(4), vec!["rfid".try_into().unwrap()]);
+        assert_eq!(reader.slots_with(&led), vec![0, 1, 2]);
+        assert_eq!(reader.slots_with(&[]), vec![0, 1, 2, 3, 4]);
+        assert_eq!(reader.validate(), Ok(()));
+    }
+
+    #[test]
+    fn it_rejects_invalid_slot_groups() {
+        let mut reader = reader();
+        let mut groups = reader.slots().clone();
+        groups[0].set_slots(vec![4, 5]);
+        groups[0].set_features(vec!["haptics".try_into().unwrap()]);
+        reader.set_slots(groups);
+
+        assert_eq!(
+            reader.validate(),
+            Err(vec![
+                "provider `reader`: slot 5 is outside of 5 slots".to_string(),
+                "provider `reader`: slot feature `haptics` is not in the spec".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_warns_about_deprecated_firmware_and_features() {
+        let mut reader = Peripheral::rf_card_reader().build();
+
+        assert_eq!(reader.warnings("en", None).len(), 1);
+        assert!(reader.warnings("en", None)[0]
+            .starts_with("provider `peripheral_defined_card_reader`: the `nfc` feature"));
+
+        reader.set_deprecated(Some(
+            serde_json::from_str(r#"{ "since": "2.18.0" }"#).unwrap(),
+        ));
+        assert_eq!(
+            reader.warnings("en", None)[0],
+            "`rf.card_reader` 2.18.1 is deprecated since 2.18.0"
+        );
+    }
 }
@@ -0,0 +1,596 @@
+//! Authoring manifests in Pkl, and loading what Pkl renders.
+//!
+//! The modules in `pkl/` are generated from the JSON Schema documents in `schema::manifests`,
+//! so the two never disagree. Shared definitions become typealiases and classes, and the root
+//! definition's fields become module properties, ready to be amended:
+//!
+//! ```pkl
+//! amends "pkl/GameManifest.pkl"
+//!
+//! key = "simple-battle"
+//! version = "9.8.1"
+//! ```
+//!
+//! `pkl eval` renders JSON, which `import()` deserializes and validates. Errors are reported
+//! at the path of the offending Pkl member, such as `scenarios[0].players[1].count`.
+use std::io;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use serde_path_to_error::Segment;
+
+use crate::codegen::pascal_case;
+use crate::schema::manifests;
+use crate::Manifest;
+
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "amends",
+    "as",
+    "case",
+    "class",
+    "const",
+    "delete",
+    "else",
+    "extends",
+    "external",
+    "false",
+    "fixed",
+    "for",
+    "function",
+    "hidden",
+    "if",
+    "import",
+    "in",
+    "is",
+    "let",
+    "local",
+    "module",
+    "new",
+    "nothing",
+    "null",
+    "open",
+    "out",
+    "outer",
+    "override",
+    "protected",
+    "read",
+    "record",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "trace",
+    "true",
+    "typealias",
+    "unknown",
+    "vararg",
+    "when",
+];
+
+/// Prefixes `validate()` puts on its messages, and the listing the named item is in.
+const NAMED: &[(&str, &str)] = &[("scenario", "scenarios"), ("provider", "provides")];
+
+/// Prefixes `validate()` puts on its messages, and the property they are about.
+const FIELDS: &[(&str, &str)] = &[("option defaults", "option_defaults")];
+
+pub fn game_manifest() -> String {
+    module("GameManifest", &manifests::game_manifest())
+}
+
+pub fn peripheral_manifest() -> String {
+    module("PeripheralManifest", &manifests::peripheral_manifest())
+}
+
+pub fn spec() -> String {
+    module("Spec", &manifests::spec())
+}
+
+pub fn vendor() -> String {
+    module("Vendor", &manifests::vendor())
+}
+
+/// Every module, by the file name it is published under in `pkl/`.
+pub fn all() -> Vec<(&'static str, String)> {
+    vec![
+        ("GameManifest.pkl", game_manifest()),
+        ("PeripheralManifest.pkl", peripheral_manifest()),
+        ("Spec.pkl", spec()),
+        ("Vendor.pkl", vendor()),
+    ]
+}
+
+/// Writes every module into `dir`, replacing what is there.
+pub fn export(dir: impl AsRef<Path>) -> io::Result<()> {
+    for (name, source) in all() {
+        std::fs::write(dir.as_ref().join(name), source)?;
+    }
+    Ok(())
+}
+
+/// Deserializes Pkl's JSON rendering, reporting errors at the Pkl path they came from.
+///
+/// Optional Pkl properties that were never set render as `null`, so those are dropped first.
+pub fn load<T: DeserializeOwned>(json: &str) -> Result<T, Vec<String>> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|error| vec![format!("not a Pkl JSON rendering: {}", error)])?;
+
+    serde_path_to_error::deserialize(without_nulls(value)).map_err(|error| {
+        let mut path = String::new();
+        for segment in error.path().iter() {
+            match segment {
+                Segment::Seq { index } => path.push_str(&format!("[{}]", index)),
+                Segment::Map { key } => push_member(&mut path, key),
+                Segment::Enum { .. } | Segment::Unknown => {}
+            }
+        }
+        vec![located(&path, &error.inner().to_string())]
+    })
+}
+
+/// Loads and validates a manifest, reporting validation errors at the Pkl path of the
+/// scenario or provider they are about.
+pub fn import<T: DeserializeOwned + Manifest>(json: &str) -> Result<T, Vec<String>> {
+    let manifest: T = load(json)?;
+
+    if let Err(errors) = manifest.validate() {
+        let document: Value = serde_json::from_str(json).unwrap_or_default();
+        return Err(errors
+            .iter()
+            .map(|error| locate(&document, error))
+            .collect());
+    }
+
+    Ok(manifest)
+}
+
+/// Turns `scenario `beginners`: option defaults: ...` into `scenarios[0].option_defaults: ...`
+fn locate(document: &Value, message: &str) -> String {
+    let mut path = String::new();
+    let mut node = document;
+    let mut rest = message;
+
+    'peeling: loop {
+        for (label, listing) in NAMED {
+            let named = rest
+                .strip_prefix(label)
+                .and_then(|rest| rest.strip_prefix(" `"))
+                .and_then(|rest| rest.split_once("`: "));
+            let Some((name, remainder)) = named else {
+                continue;
+            };
+            let index = node[listing]
+                .as_array()
+                .and_then(|items| items.iter().position(|item| item["name"] == name));
+            if let Some(index) = index {
+                push_member(&mut path, listing);
+                path.push_str(&format!("[{}]", index));
+                node = &node[listing][index];
+                rest = remainder;
+                continue 'peeling;
+            }
+        }
+        for (label, field) in FIELDS {
+            if let Some(remainder) = rest
+                .strip_prefix(label)
+                .and_then(|rest| rest.strip_prefix(": "))
+            {
+                push_member(&mut path, field);
+                node = &node[field];
+                rest = remainder;
+                continue 'peeling;
+            }
+        }
+        break;
+    }
+
+    located(&path, rest)
+}
+
+fn located(path: &str, message: &str) -> String {
+    if path.is_empty() {
+        message.to_string()
+    } else {
+        format!("{}: {}", path, message)
+    }
+}
+
+/// `.name` for properties, or `["pt-BR"]` for mapping keys that are not identifiers.
+fn push_member(path: &mut String, name: &str) {
+    if is_identifier(name) {
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(&identifier(name));
+    } else {
+        path.push_str(&format!("[{:?}]", name));
+    }
+}
+
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(members) => Value::Object(
+            members
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, without_nulls(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(without_nulls).collect()),
+        other => other,
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A property name, quoted with backticks when it is a keyword or not an identifier.
+fn identifier(name: &str) -> String {
+    if KEYWORDS.contains(&name) || !is_identifier(name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// The Pkl source for a schema document, with the root definition as the module's properties.
+fn module(name: &str, document: &Value) -> String {
+    let root = document["$ref"]
+        .as_str()
+        .expect("documents reference their root")
+        .trim_start_matches("#/$defs/");
+    let definitions = document["$defs"]
+        .as_object()
+        .expect("documents have definitions");
+
+    let mut translator = Translator {
+        definitions,
+        pending: vec![],
+        seen: vec![root.to_string()],
+        blocks: vec![],
+    };
+    let definition = &definitions[root];
+    let properties = translator.properties(name, definition);
+
+    while !translator.pending.is_empty() {
+        let next = translator.pending.remove(0);
+        translator.declare(&next);
+    }
+
+    let mut source = String::new();
+    source.push_str(&doc(&format!(
+        "{}\n\nGenerated from the {} JSON Schema by `manifests::pkl`.\nAmend this module to write one, and render it with `pkl eval`.",
+        definition["description"].as_str().unwrap_or(name),
+        name
+    )));
+    source.push_str(&format!("module {}\n\n", name));
+    source.push_str(&properties);
+    source.push_str(
+        "\noutput {\n  renderer = new JsonRenderer {\n    omitNullProperties = true\n  }\n}\n",
+    );
+    for block in translator.blocks {
+        source.push('\n');
+        source.push_str(&block);
+    }
+
+    source
+}
+
+struct Translator<'a> {
+    definitions: &'a Map<String, Value>,
+    /// Definitions that are used but not declared yet, in the order they were first used
+    pending: Vec<String>,
+    seen: Vec<String>,
+    /// Declarations, in the order they appear in the module
+    blocks: Vec<String>,
+}
+
+impl Translator<'_> {
+    fn declare(&mut self, definition: &str) {
+        let name = pascal_case(definition);
+        let schema = &self.definitions[definition];
+
+        if schema.get("properties").is_some() {
+            self.class(&name, schema);
+        } else {
+            let index = self.reserve();
+            let alias = self.kind(schema, &name);
+            self.blocks[index] = format!("{}typealias {} = {}\n", description(schema), name, alias);
+        }
+    }
+
+    /// Reserves a place for a declaration, so it comes before the classes nested in it.
+    fn reserve(&mut self) -> usize {
+        self.blocks.push(String::new());
+        self.blocks.len() - 1
+    }
+
+    fn class(&mut self, name: &str, schema: &Value) {
+        let index = self.reserve();
+        let body = self.properties(name, schema);
+        let body: String = body
+            .lines()
+            .map(|line| match line {
+                "" => "\n".to_string(),
+                line => format!("  {}\n", line),
+            })
+            .collect();
+
+        self.blocks[index] = format!("{}class {} {{\n{}}}\n", description(schema), name, body);
+    }
+
+    /// One property per field, optional unless the schema requires it.
+    fn properties(&mut self, owner: &str, schema: &Value) -> String {
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .map(|required| required.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let mut source = String::new();
+
+        for (index, (field, field_schema)) in schema["properties"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let mut kind = self.kind(field_schema, &format!("{}{}", owner, pascal_case(field)));
+            if !required.contains(&field.as_str()) {
+                if ["enum", "oneOf", "anyOf"]
+                    .iter()
+                    .any(|keyword| field_schema.get(keyword).is_some())
+                {
+                    kind = format!("({})", kind);
+                }
+                kind.push('?');
+            }
+
+            // Documented properties get room to breathe
+            let description = description(field_schema);
+            if index > 0 && !description.is_empty() {
+                source.push('\n');
+            }
+            source.push_str(&description);
+            if field_schema["deprecated"] == Value::Bool(true) {
+                source.push_str("@Deprecated\n");
+            }
+            source.push_str(&format!("{}: {}\n", identifier(field), kind));
+        }
+
+        source
+    }
+
+    /// The Pkl type for a schema. Objects with properties declare a class named `context`.
+    fn kind(&mut self, schema: &Value, context: &str) -> String {
+        if let Some(target) = schema.get("$ref").and_then(Value::as_str) {
+            let definition = target.trim_start_matches("#/$defs/").to_string();
+            if !self.seen.contains(&definition) {
+                self.seen.push(definition.clone());
+                self.pending.push(definition.clone());
+            }
+            return pascal_case(&definition);
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            return options
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join("|");
+        }
+        if let Some(options) = schema
+            .get("oneOf")
+            .or_else(|| schema.get("anyOf"))
+            .and_then(Value::as_array)
+        {
+            return options
+                .iter()
+                .map(|option| self.kind(option, &format!("{}{}", context, variant(option))))
+                .collect::<Vec<_>>()
+                .join("|");
+        }
+
+        let minimum = schema.get("minimum");
+        let maximum = schema.get("maximum");
+        match schema.get("type").and_then(Value::as_str) {
+            Some("object") if schema.get("properties").is_some() => {
+                self.class(context, schema);
+                context.to_string()
+            }
+            Some("object") => {
+                let key = match schema.get("propertyNames") {
+                    Some(names) => self.kind(names, context),
+                    None => "String".to_string(),
+                };
+                let value = match schema.get("additionalProperties") {
+                    Some(value) if value.is_object() => self.kind(value, context),
+                    _ => "Any".to_string(),
+                };
+                format!("Mapping<{}, {}>", key, value)
+            }
+            Some("array") => {
+                let item = self.kind(&schema["items"], context.trim_end_matches('s'));
+                let listing = format!("Listing<{}>", item);
+                match constraint("length", schema.get("minItems"), schema.get("maxItems")) {
+                    Some(constraint) => format!("{}({})", listing, constraint),
+                    None => listing,
+                }
+            }
+            Some("integer") => match (
+                minimum.and_then(Value::as_i64),
+                maximum.and_then(Value::as_i64),
+            ) {
+                (Some(0), Some(255)) => "UInt8".to_string(),
+                (Some(0), Some(65535)) => "UInt16".to_string(),
+                _ => match constraint("this", minimum, maximum) {
+                    Some(constraint) => format!("Int({})", constraint),
+                    None => "Int".to_string(),
+                },
+            },
+            Some("number") => match constraint("this", minimum, maximum) {
+                Some(constraint) => format!("Number({})", constraint),
+                None => "Number".to_string(),
+            },
+            Some("boolean") => "Boolean".to_string(),
+            Some("string") | None if schema.get("pattern").is_some() => format!(
+                "String(matches(Regex(#\"{}\"#)))",
+                schema["pattern"].as_str().unwrap_or_default()
+            ),
+            Some("string") => "String".to_string(),
+            _ => "Any".to_string(),
+        }
+    }
+}
+
+/// `length.isBetween(2, 3)`, `this >= 0`, or nothing when there are no bounds.
+fn constraint(subject: &str, minimum: Option<&Value>, maximum: Option<&Value>) -> Option<String> {
+    match (minimum, maximum) {
+        (Some(minimum), Some(maximum)) if subject == "this" => {
+            Some(format!("isBetween({}, {})", minimum, maximum))
+        }
+        (Some(minimum), Some(maximum)) => {
+            Some(format!("{}.isBetween({}, {})", subject, minimum, maximum))
+        }
+        (Some(minimum), None) => Some(format!("{} >= {}", subject, minimum)),
+        (None, Some(maximum)) => Some(format!("{} <= {}", subject, maximum)),
+        (None, None) => None,
+    }
+}
+
+/// Names an inline alternative after its fixed `type`, or else its first required field.
+fn variant(option: &Value) -> String {
+    let fixed = option["properties"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .find_map(|(_, property)| match property["enum"].as_array() {
+            Some(values) if values.len() == 1 => values[0].as_str(),
+            _ => None,
+        });
+    let first = option["required"]
+        .as_array()
+        .and_then(|required| required.first())
+        .and_then(Value::as_str);
+
+    fixed.or(first).map(pascal_case).unwrap_or_default()
+}
+
+fn description(schema: &Value) -> String {
+    schema["description"].as_str().map(doc).unwrap_or_default()
+}
+
+fn doc(text: &str) -> String {
+    text.lines()
+        .map(|line| match line {
+            "" => "///\n".to_string(),
+            line => format!("/// {}\n", line),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::examples::games::Game;
+    use common::data::serialization::Jsonable;
+
+    use crate::examples::Example;
+    use crate::game::GameManifest;
+    use crate::pkl;
+
+    fn simple_battle() -> Value {
+        serde_json::from_str(Game::simple_battle().json()).unwrap()
+    }
+
+    #[test]
+    fn it_keeps_the_pkl_directory_current() {
+        for (name, source) in pkl::all() {
+            let path = format!("{}/pkl/{}", env!("CARGO_MANIFEST_DIR"), name);
+            let published = std::fs::read_to_string(&path).unwrap_or_default();
+
+            assert!(
+                published == source,
+                "{} is out of date, regenerate it with `manifests::pkl::export(\"pkl\")`",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn it_mirrors_the_manifest_types() {
+        let game = pkl::game_manifest();
+
+        assert!(game.contains("\nmodule GameManifest\n"));
+        assert!(game.contains(
+            "typealias Range = UInt8|String(matches(Regex(#\"^\\d+(\\+|-\\d+)?(\\[(even|odd)\\])?$\"#)))"
+        ));
+        for declaration in [
+            "class Scenario {",
+            "class ScenarioPlayer {",
+            "class Requirement {",
+            "class Vendor {",
+            "class CountConstraintTerm {",
+            "  count: Range\n",
+            "  count: UInt8\n",
+            "  titles: LanguageMap?\n",
+        ] {
+            assert!(game.contains(declaration), "missing `{}`", declaration);
+        }
+
+        let spec = pkl::spec();
+        assert!(spec.contains("\n`extends`: SpecReference?\n"));
+        assert!(spec.contains("typealias DataValue = DataValueString|DataValueInteger"));
+
+        let peripheral = pkl::peripheral_manifest();
+        assert!(peripheral.contains("class ConnectionUsbHid {"));
+        assert!(peripheral.contains("  orientation: (\"landscape\"|\"portrait\")?\n"));
+    }
+
+    #[test]
+    fn it_imports_pkl_json_output() {
+        let mut expected = simple_battle();
+        expected.as_object_mut().unwrap().remove("support");
+        let mut rendered = expected.clone();
+        rendered["support"] = Value::Null;
+        rendered["scenarios"][0]["players"][0]["options"] = Value::Null;
+
+        let imported: GameManifest = pkl::import(&rendered.to_string()).unwrap();
+
+        assert_eq!(
+            imported,
+            GameManifest::from_json(&expected.to_string()).unwrap()
+        );
+        assert_eq!(imported.support(), &None);
+    }
+
+    #[test]
+    fn it_reports_errors_at_pkl_paths() {
+        let mut rendered = simple_battle();
+        rendered["scenarios"][0]["players"][0]["io"][1]["version"] = json!("four");
+
+        let errors = pkl::load::<GameManifest>(&rendered.to_string()).unwrap_err();
+        assert!(
+            errors[0].starts_with("scenarios[0].players[0].io[1].version: "),
+            "{:?}",
+            errors
+        );
+
+        let mut rendered = simple_battle();
+        let champion = rendered["scenarios"][0]["players"][0].clone();
+        rendered["scenarios"][0]["players"]
+            .as_array_mut()
+            .unwrap()
+            .push(champion);
+
+        let errors = pkl::import::<GameManifest>(&rendered.to_string()).unwrap_err();
+        assert_eq!(
+            errors,
+            vec!["scenarios[0]: player type `champion` is declared more than once"]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Conditions a `Requirement` places on a `Provider`'s static values.
+//!
+//! "screen width >= 800" is `{ property = "width", comparison = "AtLeast", value = 800 }`.
+use std::fmt::{Display, Formatter};
+
+use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use common::data::key::ValidKey;
+use common::macros::{Jsonable, Streamable, Tomlable};
+
+use crate::payload::Payload;
+
+/// How a provider's value compares to the value in a `Predicate`.
+#[derive(Tomlable, Jsonable, Streamable, Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    LessThan,
+    AtMost,
+    GreaterThan,
+    AtLeast,
+    /// The predicate's value is a list and the provider's value is in it
+    OneOf,
+}
+
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Predicate {
+    property: ValidKey,
+    comparison: Comparison,
+    value: Value,
+}
+
+impl Predicate {
+    /// Whether the predicate holds for a provider's static values.
+    ///
+    /// A provider that does not state the property never satisfies the predicate.
+    /// Ordering comparisons only apply to numbers.
+    pub fn holds(&self, values: &Payload) -> bool {
+        let actual = match values.get(&self.property.to_string()) {
+            Some(actual) => actual,
+            None => return false,
+        };
+
+        match self.comparison {
+            Comparison::Equal => actual == &self.value,
+            Comparison::NotEqual => actual != &self.value,
+            Comparison::OneOf => self
+                .value
+                .as_array()
+                .map(|allowed| allowed.contains(actual))
+                .unwrap_or(false),
+            Comparison::LessThan => compare(actual, &self.value, |a, b| a < b),
+            Comparison::AtMost => compare(actual, &self.value, |a, b| a <= b),
+            Comparison::GreaterThan => compare(actual, &self.value, |a, b| a > b),
+            Comparison::AtLeast => compare(actual, &self.value, |a, b| a >= b),
+        }
+    }
+}
+
+fn compare(actual: &Value, expected: &Value, ordering: fn(f64, f64) -> bool) -> bool {
+    match (actual.as_f64(), expected.as_f64()) {
+        (Some(actual), Some(expected)) => ordering(actual, expected),
+        _ => false,
+    }
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let comparison = match self.comparison {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::LessThan => "<",
+            Comparison::AtMost => "<=",
+            Comparison::GreaterThan => ">",
+            Comparison::AtLeast => ">=",
+            Comparison::OneOf => "in",
+        };
+
+        write!(f, "{} {} {}", self.property, comparison, self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::payload::Payload;
+    use crate::predicate::{Comparison, PredicateBuilder};
+
+    #[test]
+    fn it_compares_numbers() {
+        let predicate = PredicateBuilder::default()
+            .property("width".try_into().unwrap())
+            .comparison(Comparison::AtLeast)
+            .value(json!(800))
+            .build()
+            .unwrap();
+
+        assert!(predicate.holds(&Payload::new().with("width", 1920)));
+        assert!(!predicate.holds(&Payload::new().with("width", 640)));
+        assert!(!predicate.holds(&Payload::new().with("width", "wide")));
+        assert!(!predicate.holds(&Payload::new()));
+    }
+
+    #[test]
+    fn it_checks_membership() {
+        let predicate = PredicateBuilder::default()
+            .property("color".try_into().unwrap())
+            .comparison(Comparison::OneOf)
+            .value(json!(["blue", "green"]))
+            .build()
+            .unwrap();
+
+        assert!(predicate.holds(&Payload::new().with("color", "blue")));
+        assert!(!predicate.holds(&Payload::new().with("color", "red")));
+    }
+}
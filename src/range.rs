@@ -38,6 +38,53 @@ pub struct Range {
     modifier: Option<RangeModifier>,
 }
 
+impl Range {
+    /// The largest value this range allows, or `None` if it has no upper bound ("2+").
+    pub fn upper_bound(&self) -> Option<u8> {
+        if self.min == self.max {
+            Some(self.min)
+        } else if self.max == 0 {
+            None
+        } else {
+            Some(self.max)
+        }
+    }
+
+    /// Whether `value` falls inside this range, honoring any modifier.
+    pub fn contains(&self, value: u8) -> bool {
+        if value < self.min {
+            return false;
+        }
+
+        if let Some(max) = self.upper_bound() {
+            if value > max {
+                return false;
+            }
+        }
+
+        match &self.modifier {
+            Some(RangeModifier::Even) => value % 2 == 0,
+            Some(RangeModifier::Odd) => value % 2 == 1,
+            None => true,
+        }
+    }
+
+    /// The range in words, such as "2 or more, even" for "2+[even]".
+    pub fn describe(&self) -> String {
+        let mut description = match self.upper_bound() {
+            Some(max) if max == self.min => format!("exactly {}", max),
+            Some(max) => format!("{} to {}", self.min, max),
+            None => format!("{} or more", self.min),
+        };
+
+        if let Some(modifier) = &self.modifier {
+            description.push_str(&format!(", {}", modifier));
+        }
+
+        description
+    }
+}
+
 impl fmt::Display for Range {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut range = str!();
@@ -589,4 +636,69 @@ mod tests {
             )
         }
     }
+
+    mod yaml {
+        use std::str::FromStr;
+
+        use crate::range::tests::Container;
+        use crate::range::Range;
+
+        #[test]
+        fn it_deserializes_yaml_integers_and_strings() {
+            for (yaml, range) in [
+                ("range: 3", "3"),
+                ("range: \"3\"", "3"),
+                ("range: 2-4", "2-4"),
+                ("range: 2+[even]", "2+[even]"),
+                ("range: '1-5[odd]'", "1-5[odd]"),
+            ] {
+                let container: Container = serde_norway::from_str(yaml).expect(yaml);
+                assert_eq!(container.range, Range::from_str(range).unwrap(), "{}", yaml);
+            }
+        }
+    }
+
+    mod contains {
+        use std::str::FromStr;
+
+        use crate::range::Range;
+
+        #[test]
+        fn it_contains_an_exact_count() {
+            let range = Range::from_str("3").expect("Failed to parse range");
+
+            assert!(range.contains(3));
+            assert!(!range.contains(2));
+            assert!(!range.contains(4));
+        }
+
+        #[test]
+        fn it_contains_values_with_no_max() {
+            let range = Range::from_str("2+").expect("Failed to parse range");
+
+            assert!(!range.contains(1));
+            assert!(range.contains(2));
+            assert!(range.contains(200));
+            assert_eq!(range.upper_bound(), None);
+        }
+
+        #[test]
+        fn it_honors_modifiers() {
+            let range = Range::from_str("1-5[odd]").expect("Failed to parse range");
+
+            assert!(range.contains(1));
+            assert!(!range.contains(2));
+            assert!(range.contains(5));
+            assert!(!range.contains(7));
+        }
+
+        #[test]
+        fn it_describes_itself_in_words() {
+            let describe = |range: &str| Range::from_str(range).unwrap().describe();
+
+            assert_eq!(describe("3"), "exactly 3");
+            assert_eq!(describe("2-4"), "2 to 4");
+            assert_eq!(describe("2+[even]"), "2 or more, even");
+        }
+    }
 }
@@ -0,0 +1,347 @@
+//! Known specs, for following `extends` chains.
+//!
+//! A spec that extends another only lists what it adds. `SpecRegistry::resolve()` folds in
+//! everything it inherits, and `SpecRegistry::lineage()` lists every spec it can stand in for.
+//!
+//! The registry also remembers where specs used to be hosted. Aliases map old or mirror URLs to
+//! a spec key, so requirements and references using those URLs keep working after a move.
+use common::data::key::ValidKey;
+use common::semver::{Version, VersionReq};
+use common::url::Url;
+
+use crate::introspect::PropertyShape;
+use crate::specs::{Spec, SpecIdentifier, SpecReference};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpecRegistry {
+    specs: Vec<Spec>,
+    /// Other URLs, such as old hosting locations or mirrors, for each spec key
+    aliases: Vec<(Url, ValidKey)>,
+}
+
+impl SpecRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a spec, replacing any spec with the same key and version, such as the same spec
+    /// hosted somewhere new.
+    pub fn add(&mut self, spec: Spec) {
+        self.specs
+            .retain(|known| !(known.key() == spec.key() && known.version() == spec.version()));
+        self.specs.push(spec);
+    }
+
+    /// Makes `url` another name for the spec with `key`.
+    pub fn add_alias(&mut self, url: Url, key: ValidKey) {
+        self.aliases.retain(|(known, _)| known != &url);
+        self.aliases.push((url, key));
+    }
+
+    pub fn specs(&self) -> &[Spec] {
+        &self.specs
+    }
+
+    /// The key for an identifier, when the registry knows it.
+    ///
+    /// URLs are looked up in the aliases first, then among the registered specs. Unknown URLs
+    /// are returned unchanged.
+    pub fn identify(&self, identifier: &SpecIdentifier) -> SpecIdentifier {
+        let url = match identifier {
+            SpecIdentifier::Key(_) => return identifier.clone(),
+            SpecIdentifier::Url(url) => url,
+        };
+
+        self.aliases
+            .iter()
+            .find(|(alias, _)| alias == url)
+            .map(|(_, key)| key.clone())
+            .or_else(|| {
+                self.specs
+                    .iter()
+                    .find(|spec| spec.url() == url)
+                    .map(|spec| spec.key())
+            })
+            .map(SpecIdentifier::Key)
+            .unwrap_or_else(|| identifier.clone())
+    }
+
+    /// The spec with exactly this identity and version.
+    pub fn get(&self, identifier: &SpecIdentifier, version: &Version) -> Option<&Spec> {
+        self.matching(identifier)
+            .find(|spec| spec.version() == version)
+    }
+
+    /// The newest spec with this identity that matches a version requirement.
+    pub fn latest(&self, identifier: &SpecIdentifier, version: &VersionReq) -> Option<&Spec> {
+        self.matching(identifier)
+            .filter(|spec| version.matches(spec.version()))
+            .max_by(|a, b| a.version().cmp(b.version()))
+    }
+
+    fn matching<'a>(&'a self, identifier: &SpecIdentifier) -> impl Iterator<Item = &'a Spec> {
+        let identifier = self.identify(identifier);
+
+        self.specs
+            .iter()
+            .filter(move |spec| identifier.matches(&spec.reference()))
+    }
+
+    /// The spec followed by every ancestor it extends, nearest first.
+    pub fn lineage(&self, spec: &Spec) -> Result<Vec<SpecReference>, Vec<String>> {
+        let mut lineage = vec![spec.reference()];
+        let mut current = spec.clone();
+
+        while let Some(parent) = current.extends().clone() {
+            let parent_spec = self.parent_of(&current, &parent)?;
+            if lineage.iter().any(|seen| {
+                seen.key().as_ref() == Some(&parent_spec.key())
+                    && seen.version() == parent_spec.version()
+            }) {
+                return Err(vec![format!(
+                    "`{}` {} extends itself",
+                    parent.url(),
+                    parent.version()
+                )]);
+            }
+
+            current = parent_spec.clone();
+            lineage.push(parent_spec.reference());
+        }
+
+        Ok(lineage)
+    }
+
+    /// The spec with everything it inherits folded in.
+    ///
+    /// Inherited properties, events, commands, and the parent features named in `extends` come
+    /// first. The spec's own definitions replace inherited ones with the same key.
+    pub fn resolve(&self, spec: &Spec) -> Result<Spec, Vec<String>> {
+        self.resolve_from(spec, &mut vec![])
+    }
+
+    fn resolve_from(
+        &self,
+        spec: &Spec,
+        seen: &mut Vec<(String, Version)>,
+    ) -> Result<Spec, Vec<String>> {
+        let reference = match spec.extends() {
+            Some(reference) => reference,
+            None => return Ok(spec.clone()),
+        };
+        let parent = self.parent_of(spec, reference)?;
+
+        seen.push((spec.key().to_string(), spec.version().clone()));
+        if seen
+            .iter()
+            .any(|(key, version)| key == &parent.key().to_string() && version == parent.version())
+        {
+            return Err(vec![format!(
+                "`{}` {} extends itself",
+                reference.url(),
+                reference.version()
+            )]);
+        }
+
+        let parent = self.resolve_from(parent, seen)?;
+
+        let missing: Vec<String> = reference
+            .features()
+            .iter()
+            .filter(|key| parent.feature(key).is_none())
+            .map(|key| {
+                format!(
+                    "`{}` extends the `{}` feature, which `{}` {} does not have",
+                    spec.url(),
+                    key,
+                    parent.url(),
+                    parent.version()
+                )
+            })
+            .collect();
+        if !missing.is_empty() {
+            return Err(missing);
+        }
+
+        let mut resolved = spec.clone();
+
+        let mut state = parent.state().clone();
+        for property in spec.state() {
+            let key = PropertyShape::of(property).key;
+            state.retain(|inherited| PropertyShape::of(inherited).key != key);
+            state.push(property.clone());
+        }
+        resolved.set_state(state);
+
+        let mut events = parent.events().clone();
+        events.retain(|inherited| !spec.events().iter().any(|own| own.key() == inherited.key()));
+        events.extend(spec.events().iter().cloned());
+        resolved.set_events(events);
+
+        let mut commands = parent.commands().clone();
+        commands.retain(|inherited| {
+            !spec
+                .commands()
+                .iter()
+                .any(|own| own.key() == inherited.key())
+        });
+        commands.extend(spec.commands().iter().cloned());
+        resolved.set_commands(commands);
+
+        let mut features: Vec<_> = parent
+            .features()
+            .iter()
+            .filter(|feature| reference.features().contains(feature.key()))
+            .filter(|feature| spec.feature(feature.key()).is_none())
+            .cloned()
+            .collect();
+        features.extend(spec.features().iter().cloned());
+        resolved.set_features(features);
+
+        Ok(resolved)
+    }
+
+    /// The spec a reference points to, by key when it has one and by URL otherwise.
+    fn parent_of(&self, spec: &Spec, reference: &SpecReference) -> Result<&Spec, Vec<String>> {
+        let identifier = match reference.key() {
+            Some(key) => SpecIdentifier::Key(key.clone()),
+            None => SpecIdentifier::Url(reference.url().clone()),
+        };
+
+        self.get(&identifier, reference.version()).ok_or_else(|| {
+            vec![format!(
+                "`{}` extends `{}` {}, which is not registered",
+                spec.url(),
+                reference.url(),
+                reference.version()
+            )]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::semver::Version;
+    use common::url::Url;
+
+    use crate::examples::specs::Spec;
+    use crate::examples::Example;
+    use crate::registry::SpecRegistry;
+    use crate::specs::SpecIdentifier;
+
+    fn registry() -> SpecRegistry {
+        let mut registry = SpecRegistry::new();
+        registry.add(Spec::card().build());
+        registry.add(Spec::watertribe_card().build());
+        registry
+    }
+
+    #[test]
+    fn it_inherits_properties_and_named_features() {
+        let child = Spec::watertribe_card().build();
+
+        let resolved = registry().resolve(&child).expect("Failed to resolve spec");
+
+        let features: Vec<String> = resolved
+            .features()
+            .iter()
+            .map(|feature| feature.key().to_string())
+            .collect();
+        assert_eq!(features, vec!["rfid", "facing", "foil"]);
+        assert_eq!(resolved.state().len(), 2);
+        assert_eq!(resolved.events().len(), 1);
+        assert_eq!(resolved.extends(), child.extends());
+    }
+
+    #[test]
+    fn it_lists_every_ancestor() {
+        let child = Spec::watertribe_card().build();
+
+        let urls: Vec<String> = registry()
+            .lineage(&child)
+            .expect("Failed to follow lineage")
+            .iter()
+            .map(|reference| reference.url().to_string())
+            .collect();
+
+        assert_eq!(
+            urls,
+            vec![
+                "https://watertribe.example/specs/card.json",
+                "https://ravenfire.games/dev/specs/card.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_missing_and_circular_parents() {
+        let child = Spec::watertribe_card().build();
+        let mut circular = Spec::card().build();
+        circular.set_extends(Some(child.reference()));
+        let mut registry = SpecRegistry::new();
+        registry.add(child.clone());
+
+        assert_eq!(
+            registry.resolve(&child),
+            Err(vec![
+                "`https://watertribe.example/specs/card.json` extends `https://ravenfire.games/dev/specs/card.json` 1.2.3, which is not registered"
+                    .to_string()
+            ])
+        );
+
+        registry.add(circular);
+        assert!(registry.resolve(&child).is_err());
+        assert!(registry.lineage(&child).is_err());
+        assert_eq!(
+            registry
+                .latest(
+                    &SpecIdentifier::Url(child.url().clone()),
+                    &"^1".parse().unwrap()
+                )
+                .map(|spec| spec.version()),
+            Some(&Version::parse("1.0.0").unwrap())
+        );
+    }
+
+    #[test]
+    fn it_resolves_old_urls_through_aliases() {
+        let mut registry = registry();
+        let old = Url::parse("https://ravenfire.games/specs/card.json").unwrap();
+        registry.add_alias(old.clone(), "ravenfire.card".try_into().unwrap());
+
+        let card = registry
+            .get(&SpecIdentifier::Url(old), &Version::parse("1.2.3").unwrap())
+            .expect("Failed to find card by its old URL");
+
+        assert_eq!(card.key().to_string(), "ravenfire.card");
+        assert_eq!(
+            registry.identify(&SpecIdentifier::Url(card.url().clone())),
+            SpecIdentifier::Key("ravenfire.card".try_into().unwrap())
+        );
+    }
+
+    #[test]
+    fn it_replaces_specs_that_moved() {
+        let mut registry = registry();
+        let mut moved = Spec::card().build();
+        moved.set_url(Url::parse("https://docs.ravenfire.games/specs/card.json").unwrap());
+        registry.add(moved.clone());
+
+        let card = registry
+            .get(
+                &SpecIdentifier::Key("ravenfire.card".try_into().unwrap()),
+                moved.version(),
+            )
+            .expect("Failed to find card by its key");
+
+        assert_eq!(card.url(), moved.url());
+        assert_eq!(
+            registry
+                .specs()
+                .iter()
+                .filter(|spec| spec.key() == moved.key())
+                .count(),
+            1
+        );
+    }
+}
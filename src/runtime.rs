@@ -0,0 +1,155 @@
+//! Compatibility between manifests and the Raven Fire runtime a host is running.
+//!
+//! Games and peripherals declare the runtime versions and capabilities they need. A host
+//! describes itself as a `Runtime` and uses it to filter out anything it cannot run.
+use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
+
+use common::data::key::ValidKey;
+use common::macros::{Jsonable, Streamable, Tomlable};
+use common::semver::{Version, VersionReq};
+
+/// The runtime (and protocol) a host provides.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Runtime {
+    version: Version,
+    /// Optional runtime features, such as "haptics" or "remote_play"
+    #[serde(default = "Vec::default")]
+    #[builder(default)]
+    capabilities: Vec<ValidKey>,
+}
+
+/// Anything that declares which runtime it needs.
+pub trait RuntimeDependent {
+    /// Used to name the manifest in explanations
+    fn runtime_key(&self) -> String;
+    fn runtime_requirement(&self) -> &VersionReq;
+    fn capability_requirements(&self) -> &[ValidKey];
+}
+
+/// A manifest the host cannot run, and why.
+#[derive(Debug, PartialEq)]
+pub struct Incompatible<'a, T> {
+    pub manifest: &'a T,
+    pub reasons: Vec<String>,
+}
+
+impl Runtime {
+    /// Checks a single manifest, explaining every reason it cannot run here.
+    pub fn check<T: RuntimeDependent>(&self, manifest: &T) -> Result<(), Vec<String>> {
+        let mut reasons = vec![];
+
+        if !manifest.runtime_requirement().matches(&self.version) {
+            reasons.push(format!(
+                "`{}` requires runtime `{}`, but this host runs `{}`",
+                manifest.runtime_key(),
+                manifest.runtime_requirement(),
+                self.version
+            ));
+        }
+
+        for capability in manifest.capability_requirements() {
+            if !self.capabilities.contains(capability) {
+                reasons.push(format!(
+                    "`{}` requires the `{}` runtime capability",
+                    manifest.runtime_key(),
+                    capability
+                ));
+            }
+        }
+
+        if reasons.is_empty() {
+            Ok(())
+        } else {
+            Err(reasons)
+        }
+    }
+
+    /// Splits manifests into the ones this host can run and the ones it cannot.
+    pub fn partition<'a, T: RuntimeDependent>(
+        &self,
+        manifests: &'a [T],
+    ) -> (Vec<&'a T>, Vec<Incompatible<'a, T>>) {
+        let mut compatible = vec![];
+        let mut incompatible = vec![];
+
+        for manifest in manifests {
+            match self.check(manifest) {
+                Ok(()) => compatible.push(manifest),
+                Err(reasons) => incompatible.push(Incompatible { manifest, reasons }),
+            }
+        }
+
+        (compatible, incompatible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::examples::games::Game;
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::Example;
+    use crate::runtime::{Runtime, RuntimeBuilder};
+
+    fn host(version: &str) -> Runtime {
+        RuntimeBuilder::default()
+            .version(version.parse().unwrap())
+            .capabilities(vec!["haptics".try_into().unwrap()])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn it_accepts_manifests_without_requirements() {
+        assert_eq!(host("1.0.0").check(&Game::simple_battle().build()), Ok(()));
+        assert_eq!(
+            host("1.0.0").check(&Peripheral::rf_card_reader().build()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn it_filters_out_incompatible_games() {
+        let old = Game::min().build();
+        let mut new = Game::simple_battle().build();
+        new.set_requires_runtime(">=2.1".parse().unwrap());
+        new.set_requires_capabilities(vec!["remote_play".try_into().unwrap()]);
+
+        let games = vec![old.clone(), new];
+        let (compatible, incompatible) = host("2.0.3").partition(&games);
+
+        assert_eq!(compatible, vec![&old]);
+        assert_eq!(
+            incompatible[0].reasons,
+            vec![
+                "`simple-battle` requires runtime `>=2.1`, but this host runs `2.0.3`".to_string(),
+                "`simple-battle` requires the `remote_play` runtime capability".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_filters_out_incompatible_peripherals() {
+        let mut reader = Peripheral::rf_card_reader().build();
+        reader.set_requires_runtime("^3".parse().unwrap());
+
+        let peripherals = vec![reader];
+        let (compatible, incompatible) = host("2.0.3").partition(&peripherals);
+
+        assert!(compatible.is_empty());
+        assert_eq!(incompatible.len(), 1);
+    }
+}
@@ -0,0 +1,277 @@
+//! JSON Schema documents, so editors can autocomplete manifests and flag typos as they are typed.
+//!
+//! The schemas are written by hand from the same rules the deserializers follow, including the
+//! `Range` grammar and the key, locale, and semver formats. They are stricter than serde in one
+//! way: unknown fields are errors, since an unknown field is almost always a misspelled one.
+//!
+//! `manifests` covers the files authors write. `payloads` covers what devices and games send
+//! each other at runtime, for one spec and feature set.
+use serde_json::{json, Map, Value};
+
+pub mod manifests;
+pub mod payloads;
+
+/// The JSON Schema dialect every document declares.
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// "2", "2-4", "2+", and any of those followed by "[even]" or "[odd]"
+pub const RANGE_PATTERN: &str = r"^\d+(\+|-\d+)?(\[(even|odd)\])?$";
+
+/// "ravenfire.card", "simple-battle", "game_master", "_"
+pub const KEY_PATTERN: &str = r"^[A-Za-z0-9_][A-Za-z0-9_.-]*$";
+
+/// Language codes used in a `LanguageMap`, such as "en" or "pt-BR"
+pub const LOCALE_PATTERN: &str = r"^[a-z]{2,3}(-[A-Za-z0-9]{2,8})*$";
+
+/// A full semantic version, such as "1.2.3" or "10.1.2-alpha", from semver.org
+pub const VERSION_PATTERN: &str = r"^(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)(-((0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)(\.(0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?(\+([0-9a-zA-Z-]+(\.[0-9a-zA-Z-]+)*))?$";
+
+/// A version without its patch number, such as "4.10", which abbreviated manifests allow
+pub const SHORT_VERSION_PATTERN: &str = r"^(0|[1-9]\d*)\.(0|[1-9]\d*)$";
+
+/// A single version comparator, such as ">=1.2.3", "^4.0", or "*"
+const COMPARATOR_PATTERN: &str = r"(\*|(=|>=?|<=?|~|\^)?\s*\d+(\.(\d+|\*|[xX]))?(\.(\d+|\*|[xX]))?(-[0-9A-Za-z.-]+)?(\+[0-9A-Za-z.-]+)?)";
+
+/// Comma separated comparators, such as ">=1.2, <2"
+pub fn version_req_pattern() -> String {
+    format!(
+        r"^\s*{}(\s*,\s*{})*\s*$",
+        COMPARATOR_PATTERN, COMPARATOR_PATTERN
+    )
+}
+
+/// A `$ref` to one of the shared definitions.
+pub(crate) fn reference(definition: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", definition) })
+}
+
+/// An array of one of the shared definitions.
+pub(crate) fn array_of(definition: &str) -> Value {
+    json!({ "type": "array", "items": reference(definition) })
+}
+
+/// An object that only allows the listed properties.
+pub(crate) fn object(description: &str, properties: Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// Definitions for the types every manifest shares, most of them owned by `common`.
+pub(crate) fn definitions() -> Map<String, Value> {
+    let mut definitions = Map::new();
+
+    definitions.insert(
+        "key".to_string(),
+        json!({
+            "type": "string",
+            "description": "A key made of letters, digits, `.`, `_`, and `-`",
+            "pattern": KEY_PATTERN,
+        }),
+    );
+    definitions.insert(
+        "version".to_string(),
+        json!({
+            "type": "string",
+            "description": "A semantic version, such as \"1.2.3\"",
+            "pattern": VERSION_PATTERN,
+        }),
+    );
+    definitions.insert(
+        "version_req".to_string(),
+        json!({
+            "type": "string",
+            "description": "A semantic version requirement, such as \">=1.2.3\" or \"^4.0\"",
+            "pattern": version_req_pattern(),
+        }),
+    );
+    definitions.insert(
+        "range".to_string(),
+        json!({
+            "description": "A count: \"2\", \"2-4\", \"2+\", or any of those with \"[even]\" or \"[odd]\"",
+            "oneOf": [
+                { "type": "integer", "minimum": 0, "maximum": 255 },
+                { "type": "string", "pattern": RANGE_PATTERN },
+            ],
+        }),
+    );
+    definitions.insert(
+        "url".to_string(),
+        json!({ "type": "string", "format": "uri" }),
+    );
+    definitions.insert(
+        "email".to_string(),
+        json!({ "type": "string", "format": "email" }),
+    );
+    definitions.insert(
+        "date".to_string(),
+        json!({ "type": "string", "format": "date" }),
+    );
+    definitions.insert(
+        "language_map".to_string(),
+        json!({
+            "type": "object",
+            "description": "Text by language code, such as { \"en\": \"Card\", \"es\": \"Tarjeta\" }",
+            "propertyNames": { "pattern": LOCALE_PATTERN },
+            "additionalProperties": { "type": "string" },
+        }),
+    );
+    definitions.insert(
+        "payload".to_string(),
+        json!({
+            "type": "object",
+            "description": "Values keyed by property",
+        }),
+    );
+    definitions.insert(
+        "data_value".to_string(),
+        json!({
+            "oneOf": [
+                object("A string", json!({ "String": { "type": "string" } }), &["String"]),
+                object("An integer", json!({ "Integer": { "type": "integer" } }), &["Integer"]),
+                object("A number", json!({ "Float": { "type": "number" } }), &["Float"]),
+                object("A boolean", json!({ "Boolean": { "type": "boolean" } }), &["Boolean"]),
+            ],
+        }),
+    );
+    // `Property` belongs to `common`, so fields this crate does not know about are let through
+    definitions.insert(
+        "property".to_string(),
+        json!({
+            "type": "object",
+            "description": "A value a device reports or a game sends",
+            "properties": {
+                "key": reference("key"),
+                "data_type": {
+                    "enum": [
+                        "String",
+                        "Integer",
+                        "Float",
+                        "Boolean",
+                        "Playable",
+                        "Coordinate2",
+                        "Coordinate3",
+                    ],
+                },
+                "optional": { "type": "boolean" },
+                "collection": { "type": "boolean" },
+                "titles": reference("language_map"),
+                "descriptions": reference("language_map"),
+                "enumerations": array_of("data_value"),
+                "meta": array_of("property"),
+            },
+            "required": ["key", "data_type"],
+        }),
+    );
+    definitions.insert(
+        "meta".to_string(),
+        json!({
+            "type": "object",
+            "description": "Details about how the file was generated. Ignored when loading.",
+        }),
+    );
+
+    definitions
+}
+
+/// A standalone schema document for one of the definitions.
+pub(crate) fn document(title: &str, root: &str, definitions: Map<String, Value>) -> Value {
+    json!({
+        "$schema": DIALECT,
+        "title": title,
+        "$ref": format!("#/$defs/{}", root),
+        "$defs": definitions,
+    })
+}
+
+/// Every way a value breaks a schema document, as `path: reason`, according to an
+/// off-the-shelf validator.
+#[cfg(test)]
+pub(crate) fn violations(document: &Value, value: &Value) -> Vec<String> {
+    let validator = jsonschema::validator_for(document).expect("Failed to compile schema");
+
+    validator
+        .iter_errors(value)
+        .map(|error| format!("{}: {}", error.instance_path(), error))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use serde_json::json;
+
+    use common::data::key::ValidKey;
+
+    use crate::range::Range;
+    use crate::schema::{
+        definitions, document, version_req_pattern, violations, KEY_PATTERN, RANGE_PATTERN,
+        VERSION_PATTERN,
+    };
+
+    #[test]
+    fn it_matches_the_range_grammar() {
+        let pattern = Regex::new(RANGE_PATTERN).unwrap();
+
+        for range in ["2", "2-4", "2+", "2+[even]", "1-3[odd]"] {
+            assert!(pattern.is_match(range), "{} should match", range);
+            let parsed: Range = range.parse().unwrap();
+            assert!(pattern.is_match(&parsed.to_string()));
+        }
+        for range in ["two", "2-", "-4", "2+[prime]", "[even]"] {
+            assert!(!pattern.is_match(range), "{} should not match", range);
+        }
+
+        let schema = document("Range", "range", definitions());
+        assert!(violations(&schema, &json!(3)).is_empty());
+        assert!(violations(&schema, &json!("2+[even]")).is_empty());
+        assert_eq!(violations(&schema, &json!(300)).len(), 1);
+    }
+
+    #[test]
+    fn it_agrees_with_valid_keys() {
+        let pattern = Regex::new(KEY_PATTERN).unwrap();
+
+        for key in [
+            "ravenfire.card",
+            "simple-battle",
+            "game_master",
+            "peripheral_defined_card_reader",
+            "rf.card_reader",
+            "2d6",
+            "_",
+            "",
+            " ",
+            "card reader",
+            "-card",
+            ".card",
+            "card/reader",
+            "card:reader",
+            "tarjeta-ñ",
+        ] {
+            assert_eq!(
+                pattern.is_match(key),
+                ValidKey::try_from(key).is_ok(),
+                "`{}` is read differently by the schema and `ValidKey`",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn it_matches_versions_and_requirements() {
+        let version = Regex::new(VERSION_PATTERN).unwrap();
+        let requirement = Regex::new(&version_req_pattern()).unwrap();
+
+        assert!(version.is_match("10.1.2-alpha"));
+        assert!(!version.is_match("4.10"));
+        for req in [">=1.2.3", "^4.0", "*", ">=1.2, <2", "~1"] {
+            assert!(requirement.is_match(req), "{} should match", req);
+        }
+        assert!(!requirement.is_match("newest"));
+    }
+}
@@ -0,0 +1,895 @@
+//! Schemas for the manifest documents authors write by hand.
+//!
+//! Each function returns a standalone document with every definition it needs under `$defs`,
+//! ready to be saved next to the manifests and referenced from `$schema` or editor settings.
+use serde_json::{json, Map, Value};
+
+use crate::schema::{array_of, definitions, document, object, reference, SHORT_VERSION_PATTERN};
+
+pub fn game_manifest() -> Value {
+    document("Game manifest", "game_manifest", manifest_definitions())
+}
+
+pub fn expansion_manifest() -> Value {
+    document(
+        "Expansion manifest",
+        "expansion_manifest",
+        manifest_definitions(),
+    )
+}
+
+pub fn peripheral_manifest() -> Value {
+    document(
+        "Peripheral manifest",
+        "peripheral_manifest",
+        manifest_definitions(),
+    )
+}
+
+/// The short form of a peripheral manifest, with the vendor given by URL and each provider's
+/// spec given by reference.
+pub fn abbreviated_peripheral_manifest() -> Value {
+    document(
+        "Abbreviated peripheral manifest",
+        "abbreviated_peripheral_manifest",
+        manifest_definitions(),
+    )
+}
+
+pub fn device_instance() -> Value {
+    document("Device", "device_instance", manifest_definitions())
+}
+
+pub fn spec() -> Value {
+    document("Spec", "spec", manifest_definitions())
+}
+
+pub fn feature() -> Value {
+    document("Spec feature", "feature", manifest_definitions())
+}
+
+pub fn vendor() -> Value {
+    document("Vendor", "vendor", manifest_definitions())
+}
+
+/// Every document, by the file name it is published under.
+pub fn all() -> Vec<(&'static str, Value)> {
+    vec![
+        ("game.schema.json", game_manifest()),
+        ("expansion.schema.json", expansion_manifest()),
+        ("peripheral.schema.json", peripheral_manifest()),
+        (
+            "peripheral-abbreviated.schema.json",
+            abbreviated_peripheral_manifest(),
+        ),
+        ("device.schema.json", device_instance()),
+        ("spec.schema.json", spec()),
+        ("feature.schema.json", feature()),
+        ("vendor.schema.json", vendor()),
+    ]
+}
+
+fn manifest_definitions() -> Map<String, Value> {
+    let mut definitions = definitions();
+
+    for (name, definition) in vendors()
+        .into_iter()
+        .chain(specs())
+        .chain(games())
+        .chain(peripherals())
+    {
+        definitions.insert(name.to_string(), definition);
+    }
+
+    definitions
+}
+
+fn vendors() -> Vec<(&'static str, Value)> {
+    vec![
+        (
+            "vendor",
+            object(
+                "Who makes a game, peripheral, or spec",
+                json!({
+                    "name": reference("key"),
+                    "titles": reference("language_map"),
+                    "descriptions": reference("language_map"),
+                    "url": reference("url"),
+                    "email": reference("email"),
+                    "support": reference("url"),
+                }),
+                &["name"],
+            ),
+        ),
+        (
+            "vendor_reference",
+            json!({
+                "description": "A vendor, or the URL of a vendor document",
+                "oneOf": [reference("url"), reference("vendor")],
+            }),
+        ),
+    ]
+}
+
+fn specs() -> Vec<(&'static str, Value)> {
+    vec![
+        (
+            "deprecation",
+            object(
+                "Why and when something is being retired",
+                json!({
+                    "since": reference("version"),
+                    "replacement": reference("key"),
+                    "removal": reference("date"),
+                    "messages": reference("language_map"),
+                }),
+                &["since"],
+            ),
+        ),
+        (
+            "deprecated_property",
+            object(
+                "A property that is being retired",
+                json!({
+                    "key": reference("key"),
+                    "since": reference("version"),
+                    "replacement": reference("key"),
+                    "removal": reference("date"),
+                    "messages": reference("language_map"),
+                }),
+                &["key", "since"],
+            ),
+        ),
+        (
+            "spec_reference",
+            object(
+                "A spec by URL and exact version, and the features used",
+                json!({
+                    "key": reference("key"),
+                    "url": reference("url"),
+                    "version": reference("version"),
+                    "features": array_of("key"),
+                }),
+                &["url", "version"],
+            ),
+        ),
+        (
+            "spec_identifier",
+            json!({
+                "description": "A spec's URL, or its key such as \"ravenfire.card\"",
+                "anyOf": [reference("url"), reference("key")],
+            }),
+        ),
+        (
+            "event",
+            object(
+                "Something momentary a device reports",
+                json!({
+                    "key": reference("key"),
+                    "titles": reference("language_map"),
+                    "descriptions": reference("language_map"),
+                    "payload": array_of("property"),
+                }),
+                &["key"],
+            ),
+        ),
+        (
+            "command",
+            object(
+                "Something a game tells a device to do",
+                json!({
+                    "key": reference("key"),
+                    "titles": reference("language_map"),
+                    "descriptions": reference("language_map"),
+                    "parameters": array_of("property"),
+                }),
+                &["key"],
+            ),
+        ),
+        (
+            "feature",
+            with_older_state(object(
+                "An optional addition to a spec",
+                json!({
+                    "key": reference("key"),
+                    "titles": reference("language_map"),
+                    "descriptions": reference("language_map"),
+                    "state": array_of("property"),
+                    "events": array_of("event"),
+                    "commands": array_of("command"),
+                    "deprecated": reference("deprecation"),
+                    "deprecated_properties": array_of("deprecated_property"),
+                }),
+                &["key"],
+            )),
+        ),
+        (
+            "spec",
+            with_older_state(object(
+                "What a kind of device reports and accepts",
+                json!({
+                    "meta": reference("meta"),
+                    "key": reference("key"),
+                    "version": reference("version"),
+                    "url": reference("url"),
+                    "vendor": reference("vendor"),
+                    "titles": reference("language_map"),
+                    "descriptions": reference("language_map"),
+                    "state": array_of("property"),
+                    "events": array_of("event"),
+                    "features": array_of("feature"),
+                    "commands": array_of("command"),
+                    "extends": reference("spec_reference"),
+                    "deprecated": reference("deprecation"),
+                    "deprecated_properties": array_of("deprecated_property"),
+                }),
+                &["version", "url", "vendor"],
+            )),
+        ),
+    ]
+}
+
+/// Accepts `state` under its older name, `properties`, but not both at once.
+fn with_older_state(mut definition: Value) -> Value {
+    definition["properties"]["properties"] = json!({
+        "description": "The older name for `state`",
+        "deprecated": true,
+        "type": "array",
+        "items": reference("property"),
+    });
+    definition["not"] = json!({ "required": ["state", "properties"] });
+    definition
+}
+
+fn games() -> Vec<(&'static str, Value)> {
+    let requirements = json!({
+        "io": array_of("requirement"),
+        "playables": array_of("requirement"),
+    });
+
+    vec![
+        (
+            "predicate",
+            object(
+                "A condition on a provider's static values, such as \"width >= 800\"",
+                json!({
+                    "property": reference("key"),
+                    "comparison": {
+                        "enum": [
+                            "Equal",
+                            "NotEqual",
+                            "LessThan",
+                            "AtMost",
+                            "GreaterThan",
+                            "AtLeast",
+                            "OneOf",
+                        ],
+                    },
+                    "value": {},
+                }),
+                &["property", "comparison", "value"],
+            ),
+        ),
+        (
+            "requirement",
+            object(
+                "Slots a player needs from a spec",
+                json!({
+                    "name": reference("key"),
+                    "spec": reference("spec_identifier"),
+                    "version": reference("version_req"),
+                    "count": { "type": "integer", "minimum": 0, "maximum": 255 },
+                    "features": array_of("key"),
+                    "predicates": array_of("predicate"),
+                }),
+                &["name", "spec", "version", "count"],
+            ),
+        ),
+        (
+            "scenario_player",
+            object(
+                "A type of player in a scenario",
+                merge(
+                    json!({
+                        "name": reference("key"),
+                        "titles": reference("language_map"),
+                        "descriptions": reference("language_map"),
+                        "count": reference("range"),
+                    }),
+                    &requirements,
+                ),
+                &["name", "count"],
+            ),
+        ),
+        (
+            "team",
+            object(
+                "Players that play together",
+                json!({
+                    "name": reference("key"),
+                    "titles": reference("language_map"),
+                    "descriptions": reference("language_map"),
+                    "players": array_of("key"),
+                    "size": reference("range"),
+                    "count": reference("range"),
+                }),
+                &["name", "size", "count"],
+            ),
+        ),
+        (
+            "count_constraint",
+            object(
+                "A rule between player type counts: sum(terms) <relation> constant",
+                json!({
+                    "descriptions": reference("language_map"),
+                    "terms": {
+                        "type": "array",
+                        "items": object(
+                            "coefficient × count(player)",
+                            json!({
+                                "player": reference("key"),
+                                "coefficient": { "type": "integer" },
+                            }),
+                            &["player"],
+                        ),
+                    },
+                    "relation": { "enum": ["Equal", "AtLeast", "AtMost"] },
+                    "constant": { "type": "integer" },
+                }),
+                &["terms", "relation"],
+            ),
+        ),
+        (
+            "scenario",
+            object(
+                "A way to play the game",
+                json!({
+                    "name": reference("key"),
+                    "titles": reference("language_map"),
+                    "descriptions": reference("language_map"),
+                    "players": array_of("scenario_player"),
+                    "options": array_of("property"),
+                    "option_defaults": reference("payload"),
+                    "teams": array_of("team"),
+                    "constraints": array_of("count_constraint"),
+                }),
+                &["name", "players"],
+            ),
+        ),
+        (
+            "game_manifest",
+            object(
+                "A game and the peripherals it needs",
+                json!({
+                    "meta": reference("meta"),
+                    "key": reference("key"),
+                    "titles": reference("language_map"),
+                    "descriptions": reference("language_map"),
+                    "version": reference("version"),
+                    "url": reference("url"),
+                    "support": reference("url"),
+                    "vendor": reference("vendor"),
+                    "scenarios": array_of("scenario"),
+                    "options": array_of("property"),
+                    "option_defaults": reference("payload"),
+                    "requires_runtime": reference("version_req"),
+                    "requires_capabilities": array_of("key"),
+                    "deprecated": reference("deprecation"),
+                }),
+                &["key", "version", "vendor", "scenarios"],
+            ),
+        ),
+        (
+            "scenario_patch",
+            object(
+                "Changes an expansion makes to an existing scenario",
+                json!({
+                    "scenario": reference("key"),
+                    "titles": reference("language_map"),
+                    "descriptions": reference("language_map"),
+                    "players": array_of("scenario_player"),
+                    "requirements": {
+                        "type": "array",
+                        "items": object(
+                            "Requirements to add to a player type",
+                            merge(json!({ "player": reference("key") }), &requirements),
+                            &["player"],
+                        ),
+                    },
+                }),
+                &["scenario"],
+            ),
+        ),
+        (
+            "expansion_manifest",
+            object(
+                "An expansion pack for a game",
+                json!({
+                    "meta": reference("meta"),
+                    "key": reference("key"),
+                    "titles": reference("language_map"),
+                    "descriptions": reference("language_map"),
+                    "version": reference("version"),
+                    "vendor": reference("vendor"),
+                    "base": object(
+                        "The game this expands",
+                        json!({
+                            "key": reference("key"),
+                            "version": reference("version_req"),
+                        }),
+                        &["key", "version"],
+                    ),
+                    "scenarios": array_of("scenario"),
+                    "patches": array_of("scenario_patch"),
+                }),
+                &["key", "version", "vendor", "base"],
+            ),
+        ),
+    ]
+}
+
+fn peripherals() -> Vec<(&'static str, Value)> {
+    let coordinates = json!({ "type": "number" });
+    let hex_id = json!({ "type": "string", "pattern": "^0x[0-9a-fA-F]{1,4}$" });
+
+    let mut provider = json!({
+        "name": reference("key"),
+        "spec": reference("spec"),
+        "count": { "type": "integer", "minimum": 0, "maximum": 255 },
+        "values": reference("payload"),
+        "slots": array_of("slot_group"),
+        "positions": array_of("slot_position"),
+    });
+    let mut manifest = json!({
+        "meta": reference("meta"),
+        "key": reference("key"),
+        "version": reference("version"),
+        "vendor": reference("vendor"),
+        "titles": reference("language_map"),
+        "descriptions": reference("language_map"),
+        "url": reference("url"),
+        "support": reference("url"),
+        "provides": array_of("provider"),
+        "connections": array_of("connection"),
+        "layout": reference("layout"),
+        "requires_runtime": reference("version_req"),
+        "requires_capabilities": array_of("key"),
+        "deprecated": reference("deprecation"),
+    });
+
+    let definitions = vec![
+        (
+            "slot_group",
+            object(
+                "Slots with their own feature list",
+                json!({
+                    "slots": {
+                        "type": "array",
+                        "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+                    },
+                    "features": array_of("key"),
+                }),
+                &["slots"],
+            ),
+        ),
+        (
+            "point",
+            object(
+                "A point on the layout",
+                json!({ "x": coordinates, "y": coordinates }),
+                &["x", "y"],
+            ),
+        ),
+        (
+            "layout",
+            object(
+                "The outline of the device",
+                json!({
+                    "width": coordinates,
+                    "height": coordinates,
+                    "orientation": { "enum": ["landscape", "portrait"] },
+                }),
+                &["width", "height"],
+            ),
+        ),
+        (
+            "slot_position",
+            object(
+                "Where a slot physically is",
+                json!({
+                    "slot": { "type": "integer", "minimum": 0, "maximum": 255 },
+                    "x": coordinates,
+                    "y": coordinates,
+                    "width": coordinates,
+                    "height": coordinates,
+                    "rotation": coordinates,
+                    "labels": reference("language_map"),
+                    "indicator": reference("point"),
+                }),
+                &["slot", "x", "y", "width", "height"],
+            ),
+        ),
+        (
+            "connection",
+            json!({
+                "description": "A way a hub can reach the device",
+                "oneOf": [
+                    object(
+                        "Bluetooth Low Energy GATT services",
+                        json!({
+                            "type": { "enum": ["ble"] },
+                            "services": { "type": "array", "items": { "type": "string" } },
+                        }),
+                        &["type", "services"],
+                    ),
+                    object(
+                        "USB HID",
+                        json!({
+                            "type": { "enum": ["usb_hid"] },
+                            "vendor_id": hex_id,
+                            "product_id": hex_id,
+                        }),
+                        &["type", "vendor_id", "product_id"],
+                    ),
+                    object(
+                        "A serial port",
+                        json!({
+                            "type": { "enum": ["serial"] },
+                            "baud_rate": { "type": "integer", "minimum": 0 },
+                        }),
+                        &["type", "baud_rate"],
+                    ),
+                    object(
+                        "A network service",
+                        json!({
+                            "type": { "enum": ["tcp"] },
+                            "mdns_service": { "type": "string" },
+                            "port": { "type": "integer", "minimum": 0, "maximum": 65535 },
+                        }),
+                        &["type"],
+                    ),
+                    object(
+                        "A transport this crate does not know about yet",
+                        json!({
+                            "type": { "enum": ["other"] },
+                            "kind": reference("key"),
+                            "parameters": {
+                                "type": "object",
+                                "additionalProperties": { "type": "string" },
+                            },
+                        }),
+                        &["type", "kind"],
+                    ),
+                ],
+            }),
+        ),
+        (
+            "provider",
+            object(
+                "Slots implementing a spec",
+                provider.clone(),
+                &["name", "spec", "count"],
+            ),
+        ),
+        (
+            "peripheral_manifest",
+            object(
+                "A peripheral model",
+                manifest.clone(),
+                &["key", "version", "vendor"],
+            ),
+        ),
+        (
+            "device_instance",
+            object(
+                "A single physical peripheral",
+                json!({
+                    "uuid": reference("key"),
+                    "model": reference("key"),
+                    "model_version": reference("version"),
+                    "firmware": reference("version"),
+                    "nickname": { "type": "string" },
+                }),
+                &["uuid", "model", "model_version"],
+            ),
+        ),
+    ];
+
+    provider["spec"] = json!({ "oneOf": [reference("spec_reference"), reference("spec")] });
+    manifest["vendor"] = reference("vendor_reference");
+    manifest["provides"] = array_of("abbreviated_provider");
+    manifest["name"] = json!({
+        "$ref": "#/$defs/key",
+        "description": "The peripheral's key, in older abbreviated manifests",
+    });
+    manifest["version"] = json!({
+        "description": "The model's version, which abbreviated manifests may write without a patch number, such as \"4.10\"",
+        "anyOf": [
+            reference("version"),
+            { "type": "string", "pattern": SHORT_VERSION_PATTERN },
+        ],
+    });
+
+    definitions
+        .into_iter()
+        .chain([
+            (
+                "abbreviated_provider",
+                object(
+                    "Slots implementing a spec, given by reference",
+                    provider,
+                    &["name", "spec", "count"],
+                ),
+            ),
+            (
+                "abbreviated_peripheral_manifest",
+                object(
+                    "A peripheral model, with its vendor and specs given by reference",
+                    manifest,
+                    &["version", "vendor"],
+                ),
+            ),
+        ])
+        .collect()
+}
+
+/// The properties of two `properties` objects together.
+fn merge(mut properties: Value, more: &Value) -> Value {
+    if let (Some(properties), Some(more)) = (properties.as_object_mut(), more.as_object()) {
+        properties.extend(more.clone());
+    }
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use serde::Serialize;
+
+    use crate::examples::devices::Device;
+    use crate::examples::expansions::Expansion;
+    use crate::examples::features::Feature;
+    use crate::examples::games::Game;
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::specs::Spec;
+    use crate::examples::vendors::Vendor;
+    use crate::examples::Example;
+    use crate::schema::manifests;
+    use crate::schema::violations;
+
+    fn assert_conforms(schema: &Value, json: &str) {
+        let value: Value = serde_json::from_str(json).expect("Failed to parse example");
+        assert_eq!(violations(schema, &value), Vec::<String>::new());
+    }
+
+    #[test]
+    fn it_accepts_every_example() {
+        let games = manifests::game_manifest();
+        assert_conforms(&games, include_str!("../../examples/games/min.json"));
+        assert_conforms(
+            &games,
+            include_str!("../../examples/games/simple_battle/simple_battle.json"),
+        );
+
+        assert_conforms(
+            &manifests::expansion_manifest(),
+            include_str!(
+                "../../examples/expansions/simple_battle_dragons/simple_battle_dragons.json"
+            ),
+        );
+
+        let peripherals = manifests::peripheral_manifest();
+        assert_conforms(
+            &peripherals,
+            include_str!("../../examples/peripherals/min.json"),
+        );
+        assert_conforms(
+            &peripherals,
+            include_str!("../../examples/peripherals/rf.card_reader/rf.card_reader.json"),
+        );
+        assert_conforms(
+            &peripherals,
+            include_str!("../../examples/peripherals/rf.dice_pad/rf.dice_pad.json"),
+        );
+        assert_conforms(
+            &peripherals,
+            include_str!("../../examples/peripherals/rf.screen/rf.screen.json"),
+        );
+        assert_conforms(
+            &peripherals,
+            include_str!(
+                "../../examples/peripherals/watertribe.card_reader/watertribe.card_reader.json"
+            ),
+        );
+
+        let abbreviated = manifests::abbreviated_peripheral_manifest();
+        for json in [
+            include_str!("../../examples/peripherals/rf.card_reader/rf.card_reader-abbreviated.json"),
+            include_str!("../../examples/peripherals/rf.dice_pad/rf.dice_pad-abbreviated.json"),
+            include_str!("../../examples/peripherals/rf.screen/rf.screen-abbreviated.json"),
+            include_str!(
+                "../../examples/peripherals/watertribe.card_reader/watertribe.card_reader-abbreviated.json"
+            ),
+        ] {
+            assert_conforms(&abbreviated, json);
+        }
+
+        let devices = manifests::device_instance();
+        assert_conforms(&devices, include_str!("../../examples/devices/min.json"));
+        assert_conforms(
+            &devices,
+            include_str!("../../examples/devices/rf.card_reader.json"),
+        );
+
+        let specs = manifests::spec();
+        for json in [
+            include_str!("../../examples/specs/min.json"),
+            include_str!("../../examples/specs/card/card.json"),
+            include_str!("../../examples/specs/dice/dice.json"),
+            include_str!("../../examples/specs/screen/screen.json"),
+            include_str!("../../examples/specs/watertribe_card/watertribe_card.json"),
+        ] {
+            assert_conforms(&specs, json);
+        }
+
+        let features = manifests::feature();
+        assert_conforms(&features, Feature::min().json());
+        assert_conforms(&features, Feature::led().json());
+
+        let vendors = manifests::vendor();
+        assert_conforms(&vendors, include_str!("../../examples/vendor/min.json"));
+        assert_conforms(
+            &vendors,
+            include_str!("../../examples/vendor/ravenfire.json"),
+        );
+    }
+
+    /// A value as its struct serializes it, with every field written out. `None` fields are
+    /// left out, since the schemas describe them by leaving them optional.
+    fn serialized(value: &impl Serialize) -> Value {
+        fn strip(value: &mut Value) {
+            match value {
+                Value::Object(map) => {
+                    map.retain(|_, value| !value.is_null());
+                    map.values_mut().for_each(strip);
+                }
+                Value::Array(values) => values.iter_mut().for_each(strip),
+                _ => {}
+            }
+        }
+
+        let mut value = serde_json::to_value(value).expect("Failed to serialize example");
+        strip(&mut value);
+        value
+    }
+
+    #[test]
+    fn it_knows_every_field_the_structs_write() {
+        let documents: Vec<(Value, Value)> = vec![
+            (
+                manifests::game_manifest(),
+                serialized(&Game::simple_battle().build()),
+            ),
+            (
+                manifests::expansion_manifest(),
+                serialized(&Expansion::simple_battle_dragons().build()),
+            ),
+            (
+                manifests::peripheral_manifest(),
+                serialized(&Peripheral::rf_card_reader().build()),
+            ),
+            (
+                manifests::peripheral_manifest(),
+                serialized(&Peripheral::watertribe_card_reader().build()),
+            ),
+            (
+                manifests::device_instance(),
+                serialized(&Device::rf_card_reader().build()),
+            ),
+            (manifests::spec(), serialized(&Spec::card().build())),
+            (
+                manifests::spec(),
+                serialized(&Spec::watertribe_card().build()),
+            ),
+            (manifests::feature(), serialized(&Feature::led().build())),
+            (
+                manifests::vendor(),
+                serialized(&Vendor::ravenfire().build()),
+            ),
+        ];
+
+        for (schema, value) in documents {
+            assert_eq!(
+                violations(&schema, &value),
+                Vec::<String>::new(),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn it_accepts_short_versions_only_in_abbreviated_manifests() {
+        let manifest: Value = serde_json::from_str(include_str!(
+            "../../examples/peripherals/watertribe.card_reader/watertribe.card_reader-abbreviated.json"
+        ))
+        .unwrap();
+        assert_eq!(manifest["version"], "4.10");
+
+        let mut full = serialized(&Peripheral::watertribe_card_reader().build());
+        full["version"] = json!("4.10");
+        let errors = violations(&manifests::peripheral_manifest(), &full);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("/version: "), "{}", errors[0]);
+    }
+
+    #[test]
+    fn it_reports_typos_and_bad_ranges() {
+        let game = json!({
+            "key": "typo-game",
+            "version": "1.0.0",
+            "vendor": { "name": "ravenfire" },
+            "scenarios": [
+                {
+                    "name": "solo",
+                    "players": [{ "name": "champion", "count": "2-" }],
+                    "optoins": []
+                }
+            ]
+        });
+
+        let errors = violations(&manifests::game_manifest(), &game);
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors
+            .iter()
+            .any(|error| error.starts_with("/scenarios/0: ") && error.contains("'optoins'")));
+        assert!(errors
+            .iter()
+            .any(|error| error.starts_with("/scenarios/0/players/0/count: ")));
+    }
+
+    #[test]
+    fn it_takes_state_or_properties_but_not_both() {
+        let spec = |fields: Value| {
+            let mut spec = json!({
+                "key": "ravenfire.button",
+                "url": "https://ravenfire.games/dev/specs/button.json",
+                "version": "1.0.0",
+                "vendor": { "name": "ravenfire" }
+            });
+            for (name, value) in fields.as_object().unwrap() {
+                spec[name] = value.clone();
+            }
+            spec
+        };
+        let held = json!([{ "key": "held", "data_type": "Integer" }]);
+
+        for accepted in [
+            spec(json!({ "state": held })),
+            spec(json!({ "properties": held })),
+        ] {
+            assert!(violations(&manifests::spec(), &accepted).is_empty());
+            assert!(serde_json::from_value::<crate::specs::Spec>(accepted).is_ok());
+        }
+
+        let both = spec(json!({ "state": held, "properties": held }));
+        assert_eq!(violations(&manifests::spec(), &both).len(), 1);
+        assert!(serde_json::from_value::<crate::specs::Spec>(both).is_err());
+    }
+
+    #[test]
+    fn it_names_every_document() {
+        let names: Vec<&str> = manifests::all().iter().map(|(name, _)| *name).collect();
+
+        assert_eq!(names.len(), 8);
+        for (_, schema) in manifests::all() {
+            let root = schema["$ref"]
+                .as_str()
+                .unwrap()
+                .trim_start_matches("#/$defs/");
+            assert!(schema["$defs"].get(root).is_some());
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Schemas for what devices and games send each other at runtime.
+//!
+//! Each spec, with a chosen set of features, implies the shape of its state, event payloads,
+//! and command parameters. These documents follow the same rules as `payload::validate()`, so
+//! clients outside of Rust can check messages with off-the-shelf JSON Schema validators.
+//!
+//! Only what a spec declares ends up in its schemas. Pass a spec that extends another through
+//! `SpecRegistry::resolve()` first to include what it inherits.
+use serde_json::{json, Map, Value};
+
+use common::data::key::ValidKey;
+use common::data::schema::Property;
+
+use crate::introspect::{self, DataKind, PropertyShape};
+use crate::schema::DIALECT;
+use crate::specs::Spec;
+
+/// The state a device reports, with the spec's properties and those of the enabled features.
+pub fn state(spec: &Spec, features: &[ValidKey], locale: &str) -> Value {
+    let mut schema = properties(spec, &spec.state_with(features), locale);
+    describe(
+        &mut schema,
+        introspect::localized(spec.titles(), locale),
+        introspect::localized(spec.descriptions(), locale),
+    );
+
+    standalone(schema)
+}
+
+/// The payload of an event, if the spec or an enabled feature declares it.
+pub fn event(spec: &Spec, features: &[ValidKey], key: &str, locale: &str) -> Option<Value> {
+    let event = spec
+        .events_with(features)
+        .into_iter()
+        .find(|event| event.key().to_string() == key)?;
+
+    let mut schema = properties(spec, event.payload(), locale);
+    describe(
+        &mut schema,
+        introspect::localized(event.titles(), locale),
+        introspect::localized(event.descriptions(), locale),
+    );
+
+    Some(standalone(schema))
+}
+
+/// The parameters of a command, if the spec or an enabled feature declares it.
+pub fn command(spec: &Spec, features: &[ValidKey], key: &str, locale: &str) -> Option<Value> {
+    let command = spec
+        .commands_with(features)
+        .into_iter()
+        .find(|command| command.key().to_string() == key)?;
+
+    let mut schema = properties(spec, command.parameters(), locale);
+    describe(
+        &mut schema,
+        introspect::localized(command.titles(), locale),
+        introspect::localized(command.descriptions(), locale),
+    );
+
+    Some(standalone(schema))
+}
+
+/// Every payload in one document: `state`, `event.<key>`, and `command.<key>` under `$defs`.
+pub fn all(spec: &Spec, features: &[ValidKey], locale: &str) -> Value {
+    let mut definitions = Map::new();
+    definitions.insert("state".to_string(), state(spec, features, locale));
+
+    for event in spec.events_with(features) {
+        let key = event.key().to_string();
+        if let Some(schema) = self::event(spec, features, &key, locale) {
+            definitions.insert(format!("event.{}", key), schema);
+        }
+    }
+    for command in spec.commands_with(features) {
+        let key = command.key().to_string();
+        if let Some(schema) = self::command(spec, features, &key, locale) {
+            definitions.insert(format!("command.{}", key), schema);
+        }
+    }
+
+    // Nested definitions take their dialect from the document
+    for definition in definitions.values_mut() {
+        if let Some(definition) = definition.as_object_mut() {
+            definition.remove("$schema");
+        }
+    }
+
+    let mut document = json!({
+        "$schema": DIALECT,
+        "$defs": definitions,
+    });
+    describe(
+        &mut document,
+        introspect::localized(spec.titles(), locale),
+        introspect::localized(spec.descriptions(), locale),
+    );
+    document
+}
+
+/// An object with one field per property. Unknown fields are not allowed, and optional fields
+/// may be left out or `null`.
+fn properties(spec: &Spec, properties: &[Property], locale: &str) -> Value {
+    let mut fields = Map::new();
+    let mut required = vec![];
+
+    for property in properties {
+        let shape = PropertyShape::of(property);
+        let mut schema = value(&shape);
+
+        if shape.optional {
+            schema = json!({ "anyOf": [schema, { "type": "null" }] });
+        } else {
+            required.push(shape.key.clone());
+        }
+        describe(
+            &mut schema,
+            introspect::pick(&shape.titles, locale),
+            introspect::pick(&shape.descriptions, locale),
+        );
+        let deprecated = ValidKey::try_from(shape.key.as_str())
+            .ok()
+            .and_then(|key| spec.property_deprecation(&key))
+            .is_some();
+        if deprecated {
+            schema["deprecated"] = Value::Bool(true);
+        }
+
+        fields.insert(shape.key, schema);
+    }
+
+    json!({
+        "type": "object",
+        "properties": fields,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// The JSON shape of a property's value, as `DataKind::accepts()` reads it.
+fn value(shape: &PropertyShape) -> Value {
+    let mut single = match &shape.data_type {
+        DataKind::String => json!({ "type": "string" }),
+        DataKind::Playable => json!({ "type": "string", "description": "A playable's key" }),
+        DataKind::Integer => json!({ "type": "integer" }),
+        DataKind::Float => json!({ "type": "number" }),
+        DataKind::Boolean => json!({ "type": "boolean" }),
+        DataKind::Coordinate2 => coordinate(&["x", "y"]),
+        DataKind::Coordinate3 => coordinate(&["x", "y", "z"]),
+        DataKind::Other(_) => json!({}),
+    };
+    if !shape.enumerations.is_empty() {
+        single["enum"] = Value::Array(shape.enumerations.clone());
+    }
+
+    if shape.collection {
+        json!({ "type": "array", "items": single })
+    } else {
+        single
+    }
+}
+
+/// `{x, y[, z]}` or `[x, y[, z]]`
+fn coordinate(axes: &[&str]) -> Value {
+    let numbers: Map<String, Value> = axes
+        .iter()
+        .map(|axis| (axis.to_string(), json!({ "type": "number" })))
+        .collect();
+
+    json!({
+        "oneOf": [
+            {
+                "type": "object",
+                "properties": numbers,
+                "required": axes,
+                "additionalProperties": false,
+            },
+            {
+                "type": "array",
+                "items": { "type": "number" },
+                "minItems": axes.len(),
+                "maxItems": axes.len(),
+            },
+        ],
+    })
+}
+
+fn describe(schema: &mut Value, title: Option<String>, description: Option<String>) {
+    if let Some(title) = title {
+        schema["title"] = Value::String(title);
+    }
+    if let Some(description) = description {
+        schema["description"] = Value::String(description);
+    }
+}
+
+fn standalone(mut schema: Value) -> Value {
+    schema["$schema"] = Value::String(DIALECT.to_string());
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use common::data::key::ValidKey;
+
+    use crate::examples::specs::Spec;
+    use crate::examples::Example;
+    use crate::payload::Payload;
+    use crate::schema::{payloads, violations};
+
+    fn facing() -> Vec<ValidKey> {
+        vec!["facing".try_into().unwrap()]
+    }
+
+    #[test]
+    fn it_agrees_with_payload_validation() {
+        let spec = Spec::card().build();
+        let schema = payloads::state(&spec, &facing(), "es");
+
+        assert_eq!(schema["title"], "Tarjeta");
+        assert_eq!(
+            schema["properties"]["facing"]["enum"],
+            json!(["up", "down"])
+        );
+        assert_eq!(schema["required"], json!(["facing"]));
+
+        for payload in [
+            json!({ "facing": "up" }),
+            json!({ "facing": "down", "card": "fireball" }),
+            json!({ "facing": "up", "card": null }),
+            json!({ "facing": "sideways" }),
+            json!({ "card": "fireball" }),
+            json!({ "facing": "up", "colour": "red" }),
+            json!({ "facing": 1 }),
+        ] {
+            let parsed: Payload = serde_json::from_value(payload.clone()).unwrap();
+            assert_eq!(
+                violations(&schema, &payload).is_empty(),
+                spec.validate_payload(&facing(), &parsed).is_ok(),
+                "{} should be judged the same way",
+                payload
+            );
+        }
+    }
+
+    #[test]
+    fn it_describes_events_and_commands() {
+        let spec = Spec::card().build();
+        let indicator: Vec<ValidKey> = vec!["indicator".try_into().unwrap()];
+
+        let tapped = payloads::event(&spec, &[], "tapped", "en").unwrap();
+        assert!(violations(&tapped, &json!({ "card": "fireball" })).is_empty());
+        assert!(!violations(&tapped, &json!({})).is_empty());
+        assert_eq!(payloads::event(&spec, &[], "flipped", "en"), None);
+
+        let set_indicator = payloads::command(&spec, &indicator, "set_indicator", "en").unwrap();
+        assert!(violations(&set_indicator, &json!({ "color": "red", "brightness": 3 })).is_empty());
+        assert!(!violations(&set_indicator, &json!({ "color": "purple" })).is_empty());
+
+        let all = payloads::all(&spec, &[facing(), indicator].concat(), "en");
+        let mut definitions: Vec<&String> = all["$defs"].as_object().unwrap().keys().collect();
+        definitions.sort();
+        assert_eq!(
+            definitions,
+            vec![
+                "command.set_indicator",
+                "event.flipped",
+                "event.tapped",
+                "state"
+            ]
+        );
+        assert_eq!(all["$defs"]["state"].get("$schema"), None::<&Value>);
+    }
+}
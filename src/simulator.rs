@@ -0,0 +1,536 @@
+//! Virtual peripherals, so games can be played and tested without hardware.
+//!
+//! A `VirtualDevice` is built from a `PeripheralManifest` and has one `VirtualSlot` per provider
+//! slot. A slot reports its state, or one of the events its spec and features declare, such as
+//! `tapped`. Reports can be sent by hand, generated from what the spec declares, or played from a
+//! `Script`. Every report is checked against the slot's spec and features, and generated values
+//! come from a seeded generator, so the same seed and script always produce the same events.
+use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use common::data::key::ValidKey;
+use common::data::schema::Property;
+use common::macros::{Jsonable, Streamable, Tomlable};
+
+use crate::introspect::{DataKind, PropertyShape};
+use crate::payload::{self, Payload};
+use crate::peripheral::PeripheralManifest;
+use crate::specs::Event;
+use crate::PeripheralDefinedGroup;
+
+/// A payload reported by one slot of a virtual device.
+#[derive(Tomlable, Jsonable, Streamable, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SimulatedEvent {
+    pub provider: PeripheralDefinedGroup,
+    pub slot: u8,
+    /// The declared event, such as `tapped`. State reports have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<ValidKey>,
+    pub payload: Payload,
+}
+
+/// One step of a `Script`.
+#[derive(Tomlable, Jsonable, Streamable, Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Step {
+    /// Report exactly this payload, as the slot's state or with a declared event
+    Emit {
+        provider: PeripheralDefinedGroup,
+        slot: u8,
+        #[serde(default)]
+        event: Option<ValidKey>,
+        payload: Payload,
+    },
+    /// Report `count` generated payloads
+    Generate {
+        provider: PeripheralDefinedGroup,
+        slot: u8,
+        #[serde(default)]
+        event: Option<ValidKey>,
+        #[serde(default = "one")]
+        count: u16,
+    },
+}
+
+fn one() -> u16 {
+    1
+}
+
+/// A sequence of events to play on a `VirtualDevice`.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    PartialEq,
+    Builder,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Script {
+    /// Seeds the generator for `generate` steps
+    #[serde(default)]
+    #[builder(default)]
+    seed: u64,
+    steps: Vec<Step>,
+}
+
+/// One slot of one provider on a virtual device.
+#[derive(Debug, Clone, PartialEq, Getters)]
+#[getset(get = "pub")]
+pub struct VirtualSlot {
+    provider: PeripheralDefinedGroup,
+    slot: u8,
+    features: Vec<ValidKey>,
+    /// The spec's state merged with that of the slot's features
+    state: Vec<Property>,
+    /// The spec's events and those of the slot's features
+    events: Vec<Event>,
+}
+
+/// A simulated peripheral.
+#[derive(Debug, Clone)]
+pub struct VirtualDevice {
+    key: ValidKey,
+    slots: Vec<VirtualSlot>,
+    rng: XorShift,
+    events: Vec<SimulatedEvent>,
+}
+
+impl VirtualDevice {
+    /// A device with a slot for every slot of every provider.
+    ///
+    /// Slots get the state and events their provider's spec declares, so a spec that extends another
+    /// should be resolved through a `SpecRegistry` before it goes into the manifest.
+    pub fn new(manifest: &PeripheralManifest, seed: u64) -> Self {
+        let slots = manifest
+            .provides()
+            .iter()
+            .flat_map(|provider| {
+                (0..*provider.count()).map(move |slot| {
+                    let features = provider.features_for(slot);
+                    VirtualSlot {
+                        provider: provider.name().clone(),
+                        slot,
+                        state: provider.spec().state_with(&features),
+                        events: provider.spec().events_with(&features),
+                        features,
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            key: manifest.key().clone(),
+            slots,
+            rng: XorShift::new(seed),
+            events: vec![],
+        }
+    }
+
+    pub fn key(&self) -> &ValidKey {
+        &self.key
+    }
+
+    pub fn slots(&self) -> &[VirtualSlot] {
+        &self.slots
+    }
+
+    pub fn slot(&self, provider: &str, slot: u8) -> Option<&VirtualSlot> {
+        self.slots.iter().find(|virtual_slot| {
+            virtual_slot.provider.to_string() == provider && virtual_slot.slot == slot
+        })
+    }
+
+    /// Every event reported so far, oldest first.
+    pub fn events(&self) -> &[SimulatedEvent] {
+        &self.events
+    }
+
+    /// Reports the slot's state, if the payload matches it.
+    pub fn emit(
+        &mut self,
+        provider: &str,
+        slot: u8,
+        payload: Payload,
+    ) -> Result<SimulatedEvent, Vec<String>> {
+        self.report(provider, slot, None, payload)
+    }
+
+    /// Reports an event the slot's spec or features declare, if the payload matches the
+    /// event's.
+    pub fn emit_event(
+        &mut self,
+        provider: &str,
+        slot: u8,
+        event: &str,
+        payload: Payload,
+    ) -> Result<SimulatedEvent, Vec<String>> {
+        self.report(provider, slot, Some(event), payload)
+    }
+
+    /// Reports made up state that matches the slot's.
+    ///
+    /// Optional properties are left out about half of the time, collections hold up to
+    /// three items, and enumerated properties pick one of their enumerations.
+    pub fn generate(&mut self, provider: &str, slot: u8) -> Result<SimulatedEvent, Vec<String>> {
+        let payload = self.made_up(&self.find(provider, slot)?.state.clone());
+        self.emit(provider, slot, payload)
+    }
+
+    /// Reports a declared event with a made up payload, filled in like `generate()`.
+    pub fn generate_event(
+        &mut self,
+        provider: &str,
+        slot: u8,
+        event: &str,
+    ) -> Result<SimulatedEvent, Vec<String>> {
+        let declared = self.find_event(provider, slot, event)?.payload().clone();
+        let payload = self.made_up(&declared);
+        self.emit_event(provider, slot, event, payload)
+    }
+
+    /// Plays every step of a script, after reseeding the generator with the script's seed.
+    ///
+    /// Stops at the first step that fails.
+    pub fn run(&mut self, script: &Script) -> Result<Vec<SimulatedEvent>, Vec<String>> {
+        self.rng = XorShift::new(script.seed);
+        let mut events = vec![];
+
+        for (index, step) in script.steps.iter().enumerate() {
+            let prefix = |errors: Vec<String>| -> Vec<String> {
+                errors
+                    .into_iter()
+                    .map(|error| format!("step {}: {}", index, error))
+                    .collect()
+            };
+
+            match step {
+                Step::Emit {
+                    provider,
+                    slot,
+                    event,
+                    payload,
+                } => {
+                    let provider = provider.to_string();
+                    let event = event.as_ref().map(ValidKey::to_string);
+                    events.push(
+                        self.report(&provider, *slot, event.as_deref(), payload.clone())
+                            .map_err(prefix)?,
+                    );
+                }
+                Step::Generate {
+                    provider,
+                    slot,
+                    event,
+                    count,
+                } => {
+                    let provider = provider.to_string();
+                    for _ in 0..*count {
+                        let generated = match event {
+                            Some(event) => {
+                                self.generate_event(&provider, *slot, &event.to_string())
+                            }
+                            None => self.generate(&provider, *slot),
+                        };
+                        events.push(generated.map_err(prefix)?);
+                    }
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Checks a payload against the slot's state, or against the event's payload when there
+    /// is one, and records it.
+    fn report(
+        &mut self,
+        provider: &str,
+        slot: u8,
+        event: Option<&str>,
+        payload: Payload,
+    ) -> Result<SimulatedEvent, Vec<String>> {
+        let (expected, event) = match event {
+            Some(event) => {
+                let declared = self.find_event(provider, slot, event)?;
+                (declared.payload(), Some(declared.key().clone()))
+            }
+            None => (&self.find(provider, slot)?.state, None),
+        };
+
+        payload::validate(expected, &payload).map_err(|violations| {
+            violations
+                .into_iter()
+                .map(|violation| violation.to_string())
+                .collect::<Vec<String>>()
+        })?;
+
+        let simulated = SimulatedEvent {
+            provider: self.find(provider, slot)?.provider.clone(),
+            slot,
+            event,
+            payload,
+        };
+        self.events.push(simulated.clone());
+        Ok(simulated)
+    }
+
+    /// A payload for the given properties, with made up values.
+    fn made_up(&mut self, properties: &[Property]) -> Payload {
+        let mut payload = Payload::new();
+
+        for shape in properties.iter().map(PropertyShape::of) {
+            if shape.optional && self.rng.below(2) == 0 {
+                continue;
+            }
+
+            let value = if shape.collection {
+                let count = self.rng.below(4);
+                Value::Array((0..count).map(|_| self.value_for(&shape)).collect())
+            } else {
+                self.value_for(&shape)
+            };
+            payload = payload.with(&shape.key, value);
+        }
+
+        payload
+    }
+
+    fn find_event(&self, provider: &str, slot: u8, event: &str) -> Result<&Event, Vec<String>> {
+        self.find(provider, slot)?
+            .events
+            .iter()
+            .find(|declared| declared.key().to_string() == event)
+            .ok_or_else(|| {
+                vec![format!(
+                    "`{}` slot {} on provider `{}` has no event `{}`",
+                    self.key, slot, provider, event
+                )]
+            })
+    }
+
+    fn find(&self, provider: &str, slot: u8) -> Result<&VirtualSlot, Vec<String>> {
+        self.slot(provider, slot).ok_or_else(|| {
+            vec![format!(
+                "`{}` has no slot {} on provider `{}`",
+                self.key, slot, provider
+            )]
+        })
+    }
+
+    fn value_for(&mut self, shape: &PropertyShape) -> Value {
+        if !shape.enumerations.is_empty() {
+            let index = self.rng.below(shape.enumerations.len() as u64) as usize;
+            return shape.enumerations[index].clone();
+        }
+
+        match &shape.data_type {
+            DataKind::String => json!(format!("{}-{}", shape.key, self.rng.below(1000))),
+            DataKind::Playable => json!(format!("playable-{}", self.rng.below(1000))),
+            DataKind::Integer => json!(self.rng.below(100)),
+            DataKind::Float => json!(self.rng.unit()),
+            DataKind::Boolean => json!(self.rng.below(2) == 1),
+            DataKind::Coordinate2 => json!({ "x": self.rng.unit(), "y": self.rng.unit() }),
+            DataKind::Coordinate3 => {
+                json!({ "x": self.rng.unit(), "y": self.rng.unit(), "z": self.rng.unit() })
+            }
+            // Validation accepts any value for types it does not know, as long as one is there
+            DataKind::Other(name) => json!(format!("{}-{}", name, self.rng.below(1000))),
+        }
+    }
+}
+
+/// A small, seedable generator. Simulated values only need to be repeatable, not random.
+#[derive(Debug, Clone)]
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // Zero is the one state xorshift never leaves
+        Self(if seed == 0 {
+            0x9e37_79b9_7f4a_7c15
+        } else {
+            seed
+        })
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    /// In [0, 1), with two decimal places
+    fn unit(&mut self) -> f64 {
+        self.below(100) as f64 / 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::data::serialization::Jsonable;
+
+    use crate::examples::peripherals::Peripheral;
+    use crate::examples::properties::Property;
+    use crate::examples::specs::Spec;
+    use crate::examples::Example;
+    use crate::introspect::{DataKind, PropertyShape};
+    use crate::payload::{self, Payload};
+    use crate::simulator::{Script, VirtualDevice};
+
+    #[test]
+    fn it_exposes_every_provider_slot() {
+        let device = VirtualDevice::new(&Peripheral::watertribe_card_reader().build(), 1);
+
+        assert_eq!(device.slots().len(), 11);
+        assert_eq!(
+            device
+                .slot("peripheral_defined_card_reader", 9)
+                .unwrap()
+                .state()
+                .len(),
+            2
+        );
+        assert!(device.slot("peripheral_defined_card_reader", 10).is_none());
+    }
+
+    #[test]
+    fn it_generates_payloads_that_match_the_spec() {
+        let mut device = VirtualDevice::new(&Peripheral::watertribe_card_reader().build(), 7);
+
+        for virtual_slot in device.slots().to_vec() {
+            for _ in 0..20 {
+                let event = device
+                    .generate(&virtual_slot.provider().to_string(), *virtual_slot.slot())
+                    .expect("Failed to generate an event");
+                assert_eq!(
+                    payload::validate(virtual_slot.state(), &event.payload),
+                    Ok(())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_fills_in_properties_of_unknown_types() {
+        let mut device = VirtualDevice::new(&Peripheral::watertribe_card_reader().build(), 1);
+        let mut shape = PropertyShape::of(&Property::facing().build());
+        shape.data_type = DataKind::Other("Color".to_string());
+        shape.enumerations.clear();
+
+        let value = device.value_for(&shape);
+
+        assert!(value
+            .as_str()
+            .is_some_and(|value| value.starts_with("Color-")));
+    }
+
+    #[test]
+    fn it_rejects_payloads_outside_the_spec() {
+        let mut device = VirtualDevice::new(&Peripheral::watertribe_card_reader().build(), 1);
+
+        let errors = device
+            .emit(
+                "peripheral_defined_card_reader",
+                0,
+                Payload::new().with("facing", "sideways"),
+            )
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert!(device.events().is_empty());
+    }
+
+    #[test]
+    fn it_reports_declared_events_apart_from_state() {
+        let mut manifest = Peripheral::rf_card_reader().build();
+        let mut provides = manifest.provides().clone();
+        provides[0].set_spec(Spec::card().build());
+        manifest.set_provides(provides);
+        let mut device = VirtualDevice::new(&manifest, 3);
+
+        let tapped = device
+            .generate_event("peripheral_defined_card_reader", 0, "tapped")
+            .expect("Failed to generate an event");
+        let payload = Payload::new().with("card", "fireball");
+
+        assert_eq!(
+            tapped.event.map(|key| key.to_string()),
+            Some("tapped".to_string())
+        );
+        assert!(device
+            .emit_event(
+                "peripheral_defined_card_reader",
+                0,
+                "tapped",
+                payload.clone()
+            )
+            .is_ok());
+        assert!(device
+            .emit_event("peripheral_defined_card_reader", 0, "shaken", payload)
+            .is_err());
+        assert_eq!(device.events().len(), 2);
+    }
+
+    #[test]
+    fn it_plays_scripts_deterministically() {
+        let script = Script::from_json(
+            r#"
+            {
+                "seed": 42,
+                "steps": [
+                    {
+                        "action": "emit",
+                        "provider": "peripheral_defined_card_reader",
+                        "slot": 0,
+                        "payload": { "card": "fireball", "facing": "up" }
+                    },
+                    { "action": "generate", "provider": "peripheral_defined_dice_pad", "slot": 0, "count": 3 }
+                ]
+            }
+            "#,
+        )
+        .expect("Failed to build script");
+        let manifest = Peripheral::watertribe_card_reader().build();
+
+        let first = VirtualDevice::new(&manifest, 1).run(&script).unwrap();
+        let second = VirtualDevice::new(&manifest, 2).run(&script).unwrap();
+
+        assert_eq!(first.len(), 4);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn it_stops_scripts_at_the_first_failure() {
+        let script = Script::from_json(
+            r#"
+            {
+                "steps": [
+                    { "action": "generate", "provider": "peripheral_defined_card_reader", "slot": 12 }
+                ]
+            }
+            "#,
+        )
+        .expect("Failed to build script");
+        let mut device = VirtualDevice::new(&Peripheral::watertribe_card_reader().build(), 1);
+
+        assert_eq!(
+            device.run(&script),
+            Err(vec![
+                "step 0: `watertribe.card_reader` has no slot 12 on provider `peripheral_defined_card_reader`"
+                    .to_string()
+            ])
+        );
+    }
+}
@@ -1,6 +1,9 @@
+use std::fmt::{Display, Formatter};
+
 use getset::{Getters, Setters};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
+use common::chrono::NaiveDate;
 use common::data::key::ValidKey;
 use common::data::schema::Property;
 use common::data::LanguageMap;
@@ -8,6 +11,9 @@ use common::macros::{Jsonable, Streamable, Tomlable};
 use common::semver::Version;
 use common::url::Url;
 
+use crate::deprecation::{DeprecatedProperty, Deprecation};
+use crate::introspect::PropertyShape;
+use crate::payload::{self, Payload, Violation};
 use crate::vendor::Vendor;
 
 #[derive(
@@ -24,11 +30,45 @@ use crate::vendor::Vendor;
 )]
 #[getset(get = "pub", set = "pub")]
 pub struct SpecReference {
+    /// Preferred over `url` when present, since it survives the spec moving
+    #[serde(default)]
+    key: Option<ValidKey>,
     url: Url,
     version: Version,
+    /// Features of the referenced spec that are implemented
+    #[serde(default)]
     features: Vec<ValidKey>,
 }
 
+/// How a `Requirement` names a spec: by hosting URL or by key.
+///
+/// Written as a plain string. Anything that parses as a URL is one.
+#[derive(Tomlable, Jsonable, Streamable, Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum SpecIdentifier {
+    Url(Url),
+    Key(ValidKey),
+}
+
+impl SpecIdentifier {
+    /// Whether the identifier names the referenced spec.
+    pub fn matches(&self, reference: &SpecReference) -> bool {
+        match self {
+            SpecIdentifier::Url(url) => url == &reference.url,
+            SpecIdentifier::Key(key) => reference.key.as_ref() == Some(key),
+        }
+    }
+}
+
+impl Display for SpecIdentifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecIdentifier::Url(url) => write!(f, "{}", url),
+            SpecIdentifier::Key(key) => write!(f, "{}", key),
+        }
+    }
+}
+
 #[derive(
     Tomlable,
     Jsonable,
@@ -44,6 +84,14 @@ pub struct SpecReference {
 )]
 #[getset(get = "pub", set = "pub")]
 pub struct Spec {
+    /// Namespaced identity that stays the same wherever the spec is hosted
+    /// "ravenfire.card"
+    ///
+    /// Specs published before keys existed leave it out, see `Spec::key()`.
+    #[getset(skip)]
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<ValidKey>,
     version: Version,
     url: Url,
     vendor: Vendor,
@@ -53,12 +101,38 @@ pub struct Spec {
     #[builder(default)]
     #[serde(default)]
     descriptions: LanguageMap,
+    /// State the device holds, such as the card in a slot. Games poll these.
+    ///
+    /// Older documents call it `properties`.
+    #[builder(default)]
+    #[serde(default, alias = "properties")]
+    state: Vec<Property>,
+    /// Things that happen at a moment, such as a card being tapped. Games subscribe to these.
     #[builder(default)]
     #[serde(default)]
-    properties: Vec<Property>,
+    events: Vec<Event>,
     #[builder(default)]
     #[serde(default)]
     features: Vec<Feature>,
+    /// Actions a game can ask the device to perform
+    #[builder(default)]
+    #[serde(default)]
+    commands: Vec<Command>,
+    /// A spec this one is a superset of, such as a vendor's card spec building on ours.
+    /// Providers of this spec satisfy requirements on the parent.
+    ///
+    /// Until `SpecRegistry::resolve()` folds the parent in, the spec's accessors and
+    /// validation only know what it declares itself.
+    #[builder(default)]
+    #[serde(default)]
+    extends: Option<SpecReference>,
+    #[builder(default)]
+    #[serde(default)]
+    deprecated: Option<Deprecation>,
+    /// Properties of the spec itself that are on their way out
+    #[builder(default)]
+    #[serde(default)]
+    deprecated_properties: Vec<DeprecatedProperty>,
 }
 
 /// Features are purely additive. They are used to add additional functionality to a spec.
@@ -83,6 +157,685 @@ pub struct Feature {
     titles: LanguageMap,
     #[serde(default)]
     descriptions: LanguageMap,
+    #[serde(default, alias = "properties")]
+    state: Vec<Property>,
+    #[serde(default)]
+    events: Vec<Event>,
+    #[serde(default)]
+    commands: Vec<Command>,
+    #[builder(default)]
+    #[serde(default)]
+    deprecated: Option<Deprecation>,
+    /// Properties this feature adds that are on their way out
+    #[builder(default)]
+    #[serde(default)]
+    deprecated_properties: Vec<DeprecatedProperty>,
+}
+
+/// Something momentary a device reports, like "card tapped" or "button pressed".
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    Builder,
+    Default,
+    PartialEq,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Event {
+    key: ValidKey,
+    #[serde(default)]
+    titles: LanguageMap,
+    #[serde(default)]
+    descriptions: LanguageMap,
+    /// What the device sends along with the event
+    #[serde(default)]
+    payload: Vec<Property>,
+}
+
+/// How a game learns about a property or event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// State, read whenever the game wants it
+    Poll,
+    /// Events, pushed to the game as they happen
+    Subscribe,
+}
+
+/// Something a game tells a device to do, like lighting an indicator or showing an image.
+#[derive(
+    Tomlable,
+    Jsonable,
+    Streamable,
+    Debug,
+    Serialize,
+    Deserialize,
+    Getters,
+    Setters,
+    Clone,
+    Builder,
+    Default,
+    PartialEq,
+)]
+#[getset(get = "pub", set = "pub")]
+pub struct Command {
+    key: ValidKey,
+    #[serde(default)]
+    titles: LanguageMap,
+    #[serde(default)]
+    descriptions: LanguageMap,
+    /// What the game sends along with the command
     #[serde(default)]
-    properties: Vec<Property>,
+    parameters: Vec<Property>,
+}
+
+impl Spec {
+    /// The declared key, or one made of the vendor name and the file name the spec is hosted
+    /// under, such as `ravenfire.card` for `https://ravenfire.games/dev/specs/card.json`.
+    pub fn key(&self) -> ValidKey {
+        if let Some(key) = &self.key {
+            return key.clone();
+        }
+
+        let stem: String = self
+            .url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .and_then(|name| name.split('.').next())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| match c {
+                'A'..='Z' | 'a'..='z' | '0'..='9' | '_' | '-' => c,
+                _ => '_',
+            })
+            .collect();
+
+        ValidKey::try_from(format!("{}.{}", self.vendor.name(), stem))
+            .unwrap_or_else(|_| self.vendor.name().clone())
+    }
+
+    pub fn set_key(&mut self, key: ValidKey) -> &mut Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn feature(&self, key: &ValidKey) -> Option<&Feature> {
+        self.features.iter().find(|feature| &feature.key == key)
+    }
+
+    /// Keys of the features the spec declares. Inherited features are included once the spec
+    /// is resolved.
+    pub fn feature_keys(&self) -> Vec<ValidKey> {
+        self.features
+            .iter()
+            .map(|feature| feature.key.clone())
+            .collect()
+    }
+
+    /// Keys of the features the spec declares, followed by the parent features its `extends`
+    /// says it implements.
+    ///
+    /// Matching only needs the keys, so this works before the spec is resolved.
+    pub fn implemented_feature_keys(&self) -> Vec<ValidKey> {
+        let mut keys = self.feature_keys();
+
+        if let Some(parent) = &self.extends {
+            for key in &parent.features {
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                }
+            }
+        }
+
+        keys
+    }
+
+    /// A reference to this exact spec and all of the features it implements.
+    pub fn reference(&self) -> SpecReference {
+        SpecReference {
+            key: Some(self.key()),
+            url: self.url.clone(),
+            version: self.version.clone(),
+            features: self.implemented_feature_keys(),
+        }
+    }
+
+    /// This spec followed by the parent it extends, if any.
+    ///
+    /// Use `SpecRegistry::lineage()` to follow longer chains.
+    pub fn lineage(&self) -> Vec<SpecReference> {
+        let mut lineage = vec![self.reference()];
+        lineage.extend(self.extends.clone());
+        lineage
+    }
+
+    /// The deprecation of a property of the spec or of any of its features.
+    pub fn property_deprecation(&self, key: &ValidKey) -> Option<&Deprecation> {
+        self.deprecated_properties
+            .iter()
+            .chain(
+                self.features
+                    .iter()
+                    .flat_map(|feature| feature.deprecated_properties.iter()),
+            )
+            .find(|property| property.key() == key)
+            .map(|property| property.deprecation())
+    }
+
+    /// Whether the spec itself or any of the given features is deprecated.
+    pub fn is_deprecated_with(&self, features: &[ValidKey]) -> bool {
+        self.deprecated.is_some()
+            || features.iter().any(|key| {
+                self.feature(key)
+                    .is_some_and(|feature| feature.deprecated.is_some())
+            })
+    }
+
+    /// What is gone by `today` among the spec itself and the given features, such as "the
+    /// `nfc` feature was removed on 2025-06-30".
+    pub fn removals(&self, features: &[ValidKey], today: NaiveDate) -> Vec<String> {
+        let mut removals = vec![];
+
+        if let Some(removal) = self.deprecated.as_ref().and_then(|d| d.removed_on(today)) {
+            removals.push(format!(
+                "spec `{}` {} was removed on {}",
+                self.key(),
+                self.version,
+                removal
+            ));
+        }
+        for key in features {
+            let deprecation = self.feature(key).and_then(|f| f.deprecated.as_ref());
+            if let Some(removal) = deprecation.and_then(|d| d.removed_on(today)) {
+                removals.push(format!("the `{}` feature was removed on {}", key, removal));
+            }
+        }
+
+        removals
+    }
+
+    /// Warnings for using the spec with the given features and properties, with deprecation
+    /// messages in `locale` and removal dates as of `today`.
+    pub fn deprecation_warnings(
+        &self,
+        features: &[ValidKey],
+        properties: &[ValidKey],
+        locale: &str,
+        today: Option<NaiveDate>,
+    ) -> Vec<String> {
+        let mut warnings = vec![];
+
+        if let Some(deprecation) = &self.deprecated {
+            warnings.push(deprecation.warning(
+                &format!("spec `{}` {}", self.key(), self.version),
+                locale,
+                today,
+            ));
+        }
+        for key in features {
+            if let Some(deprecation) = self.feature(key).and_then(|f| f.deprecated.as_ref()) {
+                warnings.push(deprecation.warning(
+                    &format!("the `{}` feature", key),
+                    locale,
+                    today,
+                ));
+            }
+        }
+        for key in properties {
+            if let Some(deprecation) = self.property_deprecation(key) {
+                warnings.push(deprecation.warning(
+                    &format!("the `{}` property", key),
+                    locale,
+                    today,
+                ));
+            }
+        }
+
+        warnings
+    }
+
+    /// The spec's own state followed by the state each listed feature adds.
+    ///
+    /// Features the spec does not declare are skipped.
+    pub fn state_with(&self, features: &[ValidKey]) -> Vec<Property> {
+        let mut state = self.state.clone();
+
+        for key in features {
+            if let Some(feature) = self.feature(key) {
+                state.extend(feature.state.iter().cloned());
+            }
+        }
+
+        state
+    }
+
+    /// Checks the state reported by a device against the spec's properties and those of the
+    /// enabled features.
+    ///
+    /// Features the spec does not declare are reported as well.
+    pub fn validate_payload(
+        &self,
+        features: &[ValidKey],
+        payload: &Payload,
+    ) -> Result<(), Vec<Violation>> {
+        let mut violations = self.unknown_features(features);
+
+        if let Err(payload_violations) = payload::validate(&self.state_with(features), payload) {
+            violations.extend(payload_violations);
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Like `validate_payload`, for a payload that is still JSON text.
+    pub fn validate_json(&self, features: &[ValidKey], json: &str) -> Result<(), Vec<Violation>> {
+        let payload: Payload = serde_json::from_str(json).map_err(|error| {
+            vec![Violation::Malformed {
+                reason: error.to_string(),
+            }]
+        })?;
+
+        self.validate_payload(features, &payload)
+    }
+
+    /// Like `validate_payload`, for a payload still in an encoded stream, such as the
+    /// `Streamable` form read off a device connection. Any serde `Deserializer` will do.
+    pub fn validate_stream<'de, D>(
+        &self,
+        features: &[ValidKey],
+        stream: D,
+    ) -> Result<(), Vec<Violation>>
+    where
+        D: Deserializer<'de>,
+    {
+        let payload = Payload::deserialize(stream).map_err(|error| {
+            vec![Violation::Malformed {
+                reason: error.to_string(),
+            }]
+        })?;
+
+        self.validate_payload(features, &payload)
+    }
+
+    /// The spec's own commands followed by the commands each listed feature adds.
+    pub fn commands_with(&self, features: &[ValidKey]) -> Vec<Command> {
+        let mut commands = self.commands.clone();
+
+        for key in features {
+            if let Some(feature) = self.feature(key) {
+                commands.extend(feature.commands.iter().cloned());
+            }
+        }
+
+        commands
+    }
+
+    /// Checks the parameters a game is about to send with a command.
+    ///
+    /// The command must be declared by the spec or one of the enabled features.
+    pub fn validate_command(
+        &self,
+        features: &[ValidKey],
+        command: &str,
+        parameters: &Payload,
+    ) -> Result<(), Vec<Violation>> {
+        let mut violations = self.unknown_features(features);
+
+        match self
+            .commands_with(features)
+            .iter()
+            .find(|declared| declared.key.to_string() == command)
+        {
+            Some(declared) => {
+                if let Err(parameter_violations) =
+                    payload::validate(&declared.parameters, parameters)
+                {
+                    violations.extend(parameter_violations);
+                }
+            }
+            None => violations.push(Violation::UnknownCommand {
+                command: command.to_string(),
+            }),
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// The spec's own events followed by the events each listed feature adds.
+    pub fn events_with(&self, features: &[ValidKey]) -> Vec<Event> {
+        let mut events = self.events.clone();
+
+        for key in features {
+            if let Some(feature) = self.feature(key) {
+                events.extend(feature.events.iter().cloned());
+            }
+        }
+
+        events
+    }
+
+    /// Checks the payload of an event reported by a device.
+    ///
+    /// The event must be declared by the spec or one of the enabled features.
+    pub fn validate_event(
+        &self,
+        features: &[ValidKey],
+        event: &str,
+        payload: &Payload,
+    ) -> Result<(), Vec<Violation>> {
+        let mut violations = self.unknown_features(features);
+
+        match self
+            .events_with(features)
+            .iter()
+            .find(|declared| declared.key.to_string() == event)
+        {
+            Some(declared) => {
+                if let Err(payload_violations) = payload::validate(&declared.payload, payload) {
+                    violations.extend(payload_violations);
+                }
+            }
+            None => violations.push(Violation::UnknownEvent {
+                event: event.to_string(),
+            }),
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Whether a game should poll or subscribe to a key, which names either a state property
+    /// or an event of the spec and enabled features.
+    pub fn delivery(&self, features: &[ValidKey], key: &str) -> Option<Delivery> {
+        let is_state = self
+            .state_with(features)
+            .iter()
+            .any(|property| PropertyShape::of(property).key == key);
+
+        if is_state {
+            Some(Delivery::Poll)
+        } else if self
+            .events_with(features)
+            .iter()
+            .any(|event| event.key.to_string() == key)
+        {
+            Some(Delivery::Subscribe)
+        } else {
+            None
+        }
+    }
+
+    fn unknown_features(&self, features: &[ValidKey]) -> Vec<Violation> {
+        let implemented = self.feature_keys();
+
+        features
+            .iter()
+            .filter(|feature| !implemented.contains(feature))
+            .map(|feature| Violation::UnknownFeature {
+                feature: feature.to_string(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::data::key::ValidKey;
+    use common::data::serialization::Jsonable;
+    use serde_json::json;
+
+    use crate::examples::specs::Spec;
+    use crate::examples::Example;
+    use crate::payload::{Payload, Violation};
+    use crate::registry::SpecRegistry;
+    use crate::specs::Delivery;
+
+    #[test]
+    fn it_validates_events_against_a_spec_and_its_features() {
+        let spec = Spec::card().build();
+        let facing = vec!["facing".try_into().unwrap()];
+        let event = r#"{ "card": "fireball", "facing": "up" }"#;
+
+        assert_eq!(spec.validate_json(&facing, event), Ok(()));
+        assert_eq!(
+            spec.validate_json(&[], event),
+            Err(vec![Violation::UnknownKey {
+                key: "facing".to_string()
+            }])
+        );
+        assert!(matches!(
+            spec.validate_json(&facing, r#"{ "card": 7, "facing": "left" }"#)
+                .unwrap_err()[..],
+            [
+                Violation::TypeMismatch { .. },
+                Violation::NotEnumerated { .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn it_rejects_unknown_features_and_malformed_events() {
+        let spec = Spec::card().build();
+        let haptics = vec!["haptics".try_into().unwrap()];
+
+        assert_eq!(
+            spec.validate_payload(&haptics, &Payload::new()),
+            Err(vec![Violation::UnknownFeature {
+                feature: "haptics".to_string()
+            }])
+        );
+        assert!(matches!(
+            spec.validate_json(&[], r#"["fireball"]"#).unwrap_err()[..],
+            [Violation::Malformed { .. }]
+        ));
+    }
+
+    #[test]
+    fn it_validates_streamed_events() {
+        let spec = Spec::card().build();
+        let facing = vec!["facing".try_into().unwrap()];
+        let event = serde_json::to_vec(&json!({ "card": "fireball", "facing": "up" })).unwrap();
+
+        assert_eq!(
+            spec.validate_stream(&facing, &mut serde_json::Deserializer::from_slice(&event)),
+            Ok(())
+        );
+        assert_eq!(
+            spec.validate_stream(&[], serde_norway::Deserializer::from_str("card: fireball")),
+            Ok(())
+        );
+        assert!(matches!(
+            spec.validate_stream(
+                &facing,
+                &mut serde_json::Deserializer::from_slice(b"[\"fire")
+            )
+            .unwrap_err()[..],
+            [Violation::Malformed { .. }]
+        ));
+    }
+
+    #[test]
+    fn it_validates_command_parameters() {
+        let spec = Spec::card().build();
+        let indicator = vec!["indicator".try_into().unwrap()];
+
+        assert_eq!(
+            spec.validate_command(
+                &indicator,
+                "set_indicator",
+                &Payload::new().with("color", "red").with("brightness", 80)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            spec.validate_command(&indicator, "set_indicator", &Payload::new()),
+            Err(vec![Violation::Missing {
+                key: "color".to_string()
+            }])
+        );
+        assert_eq!(
+            spec.validate_command(&[], "set_indicator", &Payload::new().with("color", "red")),
+            Err(vec![Violation::UnknownCommand {
+                command: "set_indicator".to_string()
+            }])
+        );
+    }
+
+    #[test]
+    fn it_separates_state_from_events() {
+        let spec = Spec::card().build();
+        let facing = vec!["facing".try_into().unwrap()];
+
+        assert_eq!(spec.delivery(&[], "card"), Some(Delivery::Poll));
+        assert_eq!(spec.delivery(&[], "tapped"), Some(Delivery::Subscribe));
+        assert_eq!(spec.delivery(&[], "flipped"), None);
+        assert_eq!(spec.delivery(&facing, "flipped"), Some(Delivery::Subscribe));
+    }
+
+    #[test]
+    fn it_validates_event_payloads() {
+        let spec = Spec::card().build();
+        let facing = vec!["facing".try_into().unwrap()];
+
+        assert_eq!(
+            spec.validate_event(&[], "tapped", &Payload::new().with("card", "fireball")),
+            Ok(())
+        );
+        assert_eq!(
+            spec.validate_event(&facing, "flipped", &Payload::new().with("facing", "left")),
+            Err(vec![Violation::NotEnumerated {
+                key: "facing".to_string(),
+                value: "left".into(),
+                allowed: vec!["up".into(), "down".into()],
+            }])
+        );
+        assert_eq!(
+            spec.validate_event(&[], "flipped", &Payload::new()),
+            Err(vec![Violation::UnknownEvent {
+                event: "flipped".to_string()
+            }])
+        );
+    }
+
+    #[test]
+    fn it_writes_state_and_reads_older_properties() {
+        let spec = crate::specs::Spec::from_json(
+            r#"
+            {
+                "key": "ravenfire.button",
+                "url": "https://ravenfire.games/dev/specs/button.json",
+                "version": "1.0.0",
+                "vendor": { "name": "ravenfire" },
+                "state": [{ "key": "held", "data_type": "Integer" }],
+                "events": [{ "key": "pressed" }]
+            }
+            "#,
+        )
+        .expect("Failed to build spec");
+
+        assert_eq!(spec.state().len(), 1);
+        assert_eq!(spec.delivery(&[], "held"), Some(Delivery::Poll));
+        assert_eq!(spec.delivery(&[], "pressed"), Some(Delivery::Subscribe));
+
+        let written: serde_json::Value = serde_json::from_str(&spec.to_json().unwrap()).unwrap();
+        assert!(written.get("state").is_some());
+        assert!(written.get("properties").is_none());
+        // The card example still calls it `properties`
+        assert_eq!(Spec::card().build().state().len(), 1);
+    }
+
+    #[test]
+    fn it_derives_keys_for_specs_published_without_one() {
+        let json = r#"
+            {
+                "url": "https://ravenfire.games/dev/specs/button.json",
+                "version": "1.0.0",
+                "vendor": { "name": "ravenfire" }
+            }
+            "#;
+        let spec = crate::specs::Spec::from_json(json).expect("Failed to build spec");
+
+        assert_eq!(spec.key().to_string(), "ravenfire.button");
+        assert_eq!(
+            crate::specs::Spec::from_json(&spec.to_json().unwrap()).unwrap(),
+            spec
+        );
+    }
+
+    #[test]
+    fn it_validates_inherited_properties_once_resolved() {
+        let child = Spec::watertribe_card().build();
+        let mut registry = SpecRegistry::new();
+        registry.add(Spec::card().build());
+        let facing: Vec<ValidKey> = vec!["facing".try_into().unwrap()];
+        let payload = Payload::new().with("facing", "up");
+
+        assert_eq!(
+            child.validate_payload(&facing, &payload),
+            Err(vec![
+                Violation::UnknownFeature {
+                    feature: "facing".to_string()
+                },
+                Violation::UnknownKey {
+                    key: "facing".to_string()
+                },
+            ])
+        );
+
+        let resolved = registry.resolve(&child).expect("Failed to resolve spec");
+        assert_eq!(resolved.validate_payload(&facing, &payload), Ok(()));
+        assert_eq!(
+            resolved.feature_keys().len(),
+            child.implemented_feature_keys().len()
+        );
+    }
+
+    #[test]
+    fn it_warns_about_deprecated_features_and_properties() {
+        let card = Spec::card().build();
+        let features = vec!["rfid".try_into().unwrap(), "nfc".try_into().unwrap()];
+
+        assert!(card.is_deprecated_with(&features));
+        assert!(!card.is_deprecated_with(&features[..1]));
+        assert_eq!(
+            card.deprecation_warnings(&features, &[], "en", None),
+            vec!["the `nfc` feature is deprecated since 1.2.0, use `rfid` instead, and will be removed on 2030-06-30: NFC readers are being replaced by RFID"]
+        );
+
+        let button = crate::specs::Spec::from_json(
+            r#"
+            {
+                "key": "ravenfire.button",
+                "url": "https://ravenfire.games/dev/specs/button.json",
+                "version": "1.1.0",
+                "vendor": { "name": "ravenfire" },
+                "properties": [{ "key": "held", "data_type": "Integer" }],
+                "deprecated_properties": [{ "key": "held", "since": "1.1.0" }]
+            }
+            "#,
+        )
+        .expect("Failed to build spec");
+
+        assert_eq!(
+            button.deprecation_warnings(&[], &["held".try_into().unwrap()], "en", None),
+            vec!["the `held` property is deprecated since 1.1.0"]
+        );
+    }
 }
@@ -0,0 +1,86 @@
+//! YAML for manifests, next to the JSON, TOML, and stream formats from `common`.
+//!
+//! The manifests read YAML through the same serde implementations as every other format, so a
+//! `count` can be written as `2`, `"2"`, or `2+[even]` there too. Enums are written as
+//! single-key maps, such as `{ String: up }`, rather than serde_norway's `!String up` tags, so a
+//! YAML manifest has the same shape as its JSON twin.
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_norway::with::singleton_map_recursive;
+
+use common::data::schema::Property;
+
+use crate::device::DeviceInstance;
+use crate::expansion::ExpansionManifest;
+use crate::game::GameManifest;
+use crate::peripheral::PeripheralManifest;
+use crate::specs::{Feature, Spec};
+use crate::vendor::Vendor;
+
+pub trait Yamlable: Serialize + DeserializeOwned {
+    fn to_yaml(&self) -> Result<String, serde_norway::Error> {
+        let mut yaml = vec![];
+        singleton_map_recursive::serialize(self, &mut serde_norway::Serializer::new(&mut yaml))?;
+        Ok(String::from_utf8(yaml).expect("serde_norway writes UTF-8"))
+    }
+
+    fn from_yaml(yaml: &str) -> Result<Self, serde_norway::Error> {
+        singleton_map_recursive::deserialize(serde_norway::Deserializer::from_str(yaml))
+    }
+}
+
+impl Yamlable for GameManifest {}
+impl Yamlable for ExpansionManifest {}
+impl Yamlable for PeripheralManifest {}
+impl Yamlable for DeviceInstance {}
+impl Yamlable for Spec {}
+impl Yamlable for Feature {}
+impl Yamlable for Vendor {}
+impl Yamlable for Property {}
+
+#[cfg(test)]
+mod tests {
+    use common::data::schema::Property as DataProperty;
+
+    use crate::examples::properties::Property;
+    use crate::examples::Example;
+    use crate::game::GameManifest;
+    use crate::yaml::Yamlable;
+
+    #[test]
+    fn it_reads_what_authors_write() {
+        let yaml = r#"
+# Counts can be plain, quoted, or numbers
+key: duel
+version: 1.0.0
+vendor:
+  name: ravenfire
+scenarios:
+  - name: duel
+    players:
+      - name: fighter
+        count: 2
+      - name: referee
+        count: "0-1"
+      - name: audience
+        count: 2+[even]
+"#;
+        let game = GameManifest::from_yaml(yaml).unwrap();
+        let counts: Vec<String> = game.scenarios()[0]
+            .players()
+            .iter()
+            .map(|player| player.count().to_string())
+            .collect();
+
+        assert_eq!(counts, vec!["2", "0-1", "2+[even]"]);
+    }
+
+    #[test]
+    fn it_writes_enums_the_way_json_does() {
+        let property = Property::facing().build();
+        let yaml = property.to_yaml().unwrap();
+
+        assert!(yaml.contains("- String: up\n"), "{}", yaml);
+        assert_eq!(DataProperty::from_yaml(&yaml).unwrap(), property);
+    }
+}